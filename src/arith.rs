@@ -0,0 +1,995 @@
+// BigInt Arithmetic: Ordering, Multiplication, Division, Decimal Conversion
+// ==========================================================================
+
+// Part 08 gave `BigInt` addition and subtraction. The number types built on top of it need the
+// rest of the usual toolkit, so this module collects it in one place.
+use bigint_ref::BigIntRef;
+use part05::BigInt;
+use std::cmp::Ordering;
+use std::{fmt, ops, str};
+
+// Part 08 spelled out the owned variants of `Add` by hand. For number types with many operators
+// that gets repetitive, so this macro forwards them to the implementation on references. It has to
+// come before the modules that use it, which is why `main.rs` declares this module first.
+macro_rules! forward_binop {
+    ($t:ty, $imp:ident, $method:ident) => {
+        impl ::std::ops::$imp for $t {
+            type Output = $t;
+            fn $method(self, rhs: $t) -> $t {
+                (&self).$method(&rhs)
+            }
+        }
+
+        impl ::std::ops::$imp<&$t> for $t {
+            type Output = $t;
+            fn $method(self, rhs: &$t) -> $t {
+                (&self).$method(rhs)
+            }
+        }
+
+        impl ::std::ops::$imp<$t> for &$t {
+            type Output = $t;
+            fn $method(self, rhs: $t) -> $t {
+                self.$method(&rhs)
+            }
+        }
+    };
+}
+
+// ## Ordering
+
+// `PartialEq` is implemented in part 07, and since every `BigInt` equals itself, `Eq` is free.
+impl Eq for BigInt {}
+
+// We deliberately do *not* implement `Ord`: its provided method `min` takes `self` by value, and
+// would then be picked over `part07::Minimum::min` in calls like `b1.min(&b2)`. An inherent `cmp`
+// gives us the same convenience without the ambiguity.
+impl BigInt {
+    pub fn cmp(&self, other: &BigInt) -> Ordering {
+        debug_assert!(self.test_invariant() && other.test_invariant());
+        // Without trailing zeros, the longer number is the larger one. Otherwise, the most
+        // significant differing digit decides.
+        self.data
+            .len()
+            .cmp(&other.data.len())
+            .then_with(|| self.data.iter().rev().cmp(other.data.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+        Some(BigInt::cmp(self, other))
+    }
+}
+
+// ## Small helpers
+
+impl From<u64> for BigInt {
+    fn from(x: u64) -> Self {
+        BigInt::new(x)
+    }
+}
+
+impl From<u128> for BigInt {
+    fn from(x: u128) -> Self {
+        BigInt::from_vec(vec![x as u64, (x >> 64) as u64])
+    }
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt::new(0)
+    }
+
+    pub fn one() -> Self {
+        BigInt::new(1)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.data.first().map(|d| d % 2 == 0).unwrap_or(true)
+    }
+
+    pub fn is_odd(&self) -> bool {
+        !self.is_even()
+    }
+
+    // Returns the value as a `u64`, if it fits.
+    pub fn to_u64(&self) -> Option<u64> {
+        match self.data.len() {
+            0 => Some(0),
+            1 => Some(self.data[0]),
+            _ => None,
+        }
+    }
+
+    // The number of bits needed to represent the value; zero needs none.
+    pub fn bits(&self) -> usize {
+        match self.data.last() {
+            None => 0,
+            Some(top) => 64 * self.data.len() - top.leading_zeros() as usize,
+        }
+    }
+
+    // Tests bit `i`, counting from the least significant bit.
+    pub fn bit(&self, i: usize) -> bool {
+        self.data
+            .get(i / 64)
+            .map(|d| (d >> (i % 64)) & 1 == 1)
+            .unwrap_or(false)
+    }
+
+    // Interprets the bytes as a big-endian number, which is how cryptographic formats store them.
+    pub fn from_bytes_be(bytes: &[u8]) -> BigInt {
+        let data = bytes
+            .rchunks(8)
+            .map(|chunk| chunk.iter().fold(0u64, |d, &b| (d << 8) | b as u64))
+            .collect();
+        BigInt::from_vec(data)
+    }
+
+    // The big-endian bytes of the value, without leading zeros. Zero becomes the empty vector.
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = self
+            .data
+            .iter()
+            .rev()
+            .flat_map(|d| d.to_be_bytes().to_vec())
+            .collect();
+        let leading = bytes.iter().take_while(|&&b| b == 0).count();
+        bytes.drain(..leading);
+        bytes
+    }
+
+    // In-place `self = self * m + a`, the workhorse of decimal parsing.
+    fn mul_add_small(&mut self, m: u64, a: u64) {
+        let mut carry = a as u128;
+        for d in self.data.iter_mut() {
+            let t = (*d as u128) * (m as u128) + carry;
+            *d = t as u64;
+            carry = t >> 64;
+        }
+        if carry != 0 {
+            self.data.push(carry as u64);
+        }
+        // Multiplying by zero may have left zero digits behind.
+        while self.data.last() == Some(&0) {
+            self.data.pop();
+        }
+    }
+
+    // Divides by a single digit, returning quotient and remainder.
+    pub fn div_rem_u64(&self, d: u64) -> (BigInt, u64) {
+        assert!(d != 0, "attempt to divide by zero");
+        let mut quotient = vec![0; self.data.len()];
+        let mut rem: u128 = 0;
+        for i in (0..self.data.len()).rev() {
+            let cur = (rem << 64) | self.data[i] as u128;
+            quotient[i] = (cur / d as u128) as u64;
+            rem = cur % d as u128;
+        }
+        (BigInt::from_vec(quotient), rem as u64)
+    }
+}
+
+// ## Shifts
+
+// Shifts a digit slice left by `s < 64` bits. The result has one extra digit for the bits shifted
+// out at the top.
+fn shl_digits(a: &[u64], s: u32) -> Vec<u64> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry = 0;
+    for &d in a {
+        if s == 0 {
+            result.push(d);
+        } else {
+            result.push((d << s) | carry);
+            carry = d >> (64 - s);
+        }
+    }
+    result.push(carry);
+    result
+}
+
+// The inverse of `shl_digits`, again for `s < 64`.
+fn shr_digits(a: &[u64], s: u32) -> Vec<u64> {
+    if s == 0 {
+        return a.to_vec();
+    }
+    (0..a.len())
+        .map(|i| {
+            let high = if i + 1 < a.len() {
+                a[i + 1] << (64 - s)
+            } else {
+                0
+            };
+            (a[i] >> s) | high
+        })
+        .collect()
+}
+
+impl ops::Shl<usize> for &BigInt {
+    type Output = BigInt;
+    fn shl(self, bits: usize) -> BigInt {
+        if self.is_zero() {
+            return BigInt::zero();
+        }
+        let mut v = vec![0; bits / 64];
+        v.extend(shl_digits(&self.data, (bits % 64) as u32));
+        BigInt::from_vec(v)
+    }
+}
+
+impl ops::Shl<usize> for BigInt {
+    type Output = BigInt;
+    fn shl(self, bits: usize) -> BigInt {
+        &self << bits
+    }
+}
+
+impl ops::Shr<usize> for &BigInt {
+    type Output = BigInt;
+    fn shr(self, bits: usize) -> BigInt {
+        if bits / 64 >= self.data.len() {
+            return BigInt::zero();
+        }
+        BigInt::from_vec(shr_digits(&self.data[bits / 64..], (bits % 64) as u32))
+    }
+}
+
+impl ops::Shr<usize> for BigInt {
+    type Output = BigInt;
+    fn shr(self, bits: usize) -> BigInt {
+        &self >> bits
+    }
+}
+
+// ## Multiplication
+
+// Schoolbook multiplication. Every partial product fits into a `u128`, together with the digit
+// already stored at that position and the carry: `(2^64-1)^2 + 2*(2^64-1) = 2^128-1`.
+fn mul_digits(a: &[u64], b: &[u64]) -> Vec<u64> {
+    if a.is_empty() || b.is_empty() {
+        return vec![];
+    }
+    let mut result = vec![0; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry: u128 = 0;
+        for (j, &y) in b.iter().enumerate() {
+            let t = (x as u128) * (y as u128) + result[i + j] as u128 + carry;
+            result[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        result[i + b.len()] = carry as u64;
+    }
+    result
+}
+
+// Below this many digits in the shorter operand, schoolbook multiplication is faster.
+const KARATSUBA_THRESHOLD: usize = 32;
+
+// Karatsuba's algorithm. We split both operands at `k` digits, `a = a0 + a1 X` and
+// `b = b0 + b1 X` with `X = 2^(64k)`. Then `a b = z0 + (z1 - z0 - z2) X + z2 X^2`, with
+// `z0 = a0 b0`, `z2 = a1 b1` and `z1 = (a0 + a1) (b0 + b1)`. That is three products of half the
+// size instead of four, which adds up to `O(n^1.58)` instead of `O(n^2)`. Splitting is where
+// borrowed `BigIntRef` views pay off: the halves are never copied.
+fn karatsuba(a: &BigIntRef, b: &BigIntRef) -> BigInt {
+    let (a, b) = (a.trimmed(), b.trimmed());
+    let (n, m) = (a.digits().len(), b.digits().len());
+    if n.min(m) < KARATSUBA_THRESHOLD {
+        return BigInt::from_vec(mul_digits(a.digits(), b.digits()));
+    }
+    let k = n.max(m) / 2;
+    let (a0, a1) = a.split_at(k);
+    let (b0, b1) = b.split_at(k);
+    let z0 = karatsuba(a0, b0);
+    let z2 = karatsuba(a1, b1);
+    let (mut sum_a, mut sum_b) = (vec![], vec![]);
+    a0.add_into(a1, &mut sum_a);
+    b0.add_into(b1, &mut sum_b);
+    let z1 = karatsuba(BigIntRef::new(&sum_a), BigIntRef::new(&sum_b));
+    // `z1 >= z0 + z2`, since it has the cross terms on top.
+    let middle = (&(&z1 - &z0).unwrap() - &z2).unwrap();
+    let mut result = z0;
+    result += &(middle << (64 * k));
+    result += &(z2 << (128 * k));
+    result
+}
+
+impl ops::Mul<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        if self.data.len().min(rhs.data.len()) < KARATSUBA_THRESHOLD {
+            BigInt::from_vec(mul_digits(&self.data, &rhs.data))
+        } else {
+            karatsuba(self, rhs)
+        }
+    }
+}
+
+impl ops::Mul for BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: BigInt) -> BigInt {
+        &self * &rhs
+    }
+}
+
+impl ops::Mul<&BigInt> for BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: &BigInt) -> BigInt {
+        &self * rhs
+    }
+}
+
+impl ops::Mul<BigInt> for &BigInt {
+    type Output = BigInt;
+    fn mul(self, rhs: BigInt) -> BigInt {
+        self * &rhs
+    }
+}
+
+impl BigInt {
+    // Exponentiation by squaring.
+    pub fn pow(&self, mut exp: u32) -> BigInt {
+        let mut base = self.clone();
+        let mut result = BigInt::one();
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = &result * &base;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = &base * &base;
+            }
+        }
+        result
+    }
+}
+
+// ## Compound assignment
+
+// Part 08's `Add` always allocates a new vector for the result. When we own the left operand, we
+// can instead add into its digits directly, and only grow the vector when needed.
+impl ops::AddAssign<&BigInt> for BigInt {
+    fn add_assign(&mut self, rhs: &BigInt) {
+        if self.data.len() < rhs.data.len() {
+            self.data.resize(rhs.data.len(), 0);
+        }
+        let mut carry = false;
+        for i in 0..self.data.len() {
+            // Once `rhs` is used up and there is no carry, the remaining digits stay as they are.
+            if i >= rhs.data.len() && !carry {
+                break;
+            }
+            let rhs_val = rhs.data.get(i).cloned().unwrap_or(0);
+            let (sum, c1) = self.data[i].overflowing_add(rhs_val);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            self.data[i] = sum;
+            carry = c1 || c2;
+        }
+        if carry {
+            self.data.push(1);
+        }
+    }
+}
+
+impl ops::AddAssign for BigInt {
+    fn add_assign(&mut self, rhs: BigInt) {
+        *self += &rhs;
+    }
+}
+
+// Multiplication needs a separate result buffer anyway, so this is just for convenience.
+impl ops::MulAssign<&BigInt> for BigInt {
+    fn mul_assign(&mut self, rhs: &BigInt) {
+        *self = &*self * rhs;
+    }
+}
+
+impl ops::MulAssign for BigInt {
+    fn mul_assign(&mut self, rhs: BigInt) {
+        *self = &*self * &rhs;
+    }
+}
+
+// ## Division
+
+// Long division of `u` by `v`, where `v` has at least two digits and no trailing zeros. This is
+// Algorithm D from Knuth's TAOCP, vol. 2, section 4.3.1: we normalize `v` such that its top bit
+// is set, which guarantees that each estimated quotient digit is off by at most two.
+fn div_rem_digits(u: &[u64], v: &[u64]) -> (Vec<u64>, Vec<u64>) {
+    let n = v.len();
+    debug_assert!(n >= 2 && v[n - 1] != 0);
+    if u.len() < n {
+        return (vec![], u.to_vec());
+    }
+    let m = u.len() - n;
+    let s = v[n - 1].leading_zeros();
+    let mut vn = shl_digits(v, s);
+    vn.pop();
+    let mut un = shl_digits(u, s);
+    let mut q = vec![0; m + 1];
+    let b: u128 = 1 << 64;
+
+    for j in (0..=m).rev() {
+        // Estimate the next quotient digit from the top two digits of the remainder.
+        let num = ((un[j + n] as u128) << 64) | un[j + n - 1] as u128;
+        let mut qhat = num / vn[n - 1] as u128;
+        let mut rhat = num % vn[n - 1] as u128;
+        while qhat >= b || qhat * vn[n - 2] as u128 > ((rhat << 64) | un[j + n - 2] as u128) {
+            qhat -= 1;
+            rhat += vn[n - 1] as u128;
+            if rhat >= b {
+                break;
+            }
+        }
+
+        // Multiply and subtract `qhat * vn` from the current window of `un`.
+        let mut carry: u128 = 0;
+        let mut borrow = false;
+        for i in 0..n {
+            let p = qhat * vn[i] as u128 + carry;
+            carry = p >> 64;
+            let (d, b1) = un[i + j].overflowing_sub(p as u64);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+            un[i + j] = d;
+            borrow = b1 || b2;
+        }
+        let (d, b1) = un[j + n].overflowing_sub(carry as u64);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        un[j + n] = d;
+
+        // If that went negative, `qhat` was one too large: add `vn` back.
+        if b1 || b2 {
+            qhat -= 1;
+            let mut carry = false;
+            for i in 0..n {
+                let (d, c1) = un[i + j].overflowing_add(vn[i]);
+                let (d, c2) = d.overflowing_add(carry as u64);
+                un[i + j] = d;
+                carry = c1 || c2;
+            }
+            un[j + n] = un[j + n].wrapping_add(carry as u64);
+        }
+        q[j] = qhat as u64;
+    }
+
+    (q, shr_digits(&un[..n], s))
+}
+
+impl BigInt {
+    // Computes quotient and remainder at once. Like `Sub`, we return `None` instead of panicking
+    // when the operation is undefined, i.e., for a zero divisor.
+    pub fn div_rem(&self, divisor: &BigInt) -> Option<(BigInt, BigInt)> {
+        match divisor.data.len() {
+            0 => None,
+            1 => {
+                let (q, r) = self.div_rem_u64(divisor.data[0]);
+                Some((q, BigInt::new(r)))
+            }
+            _ => {
+                let (q, r) = div_rem_digits(&self.data, &divisor.data);
+                Some((BigInt::from_vec(q), BigInt::from_vec(r)))
+            }
+        }
+    }
+
+    // The greatest common divisor, by Euclid's algorithm. `gcd(0, 0)` is zero.
+    pub fn gcd(&self, other: &BigInt) -> BigInt {
+        let mut a = self.clone();
+        let mut b = other.clone();
+        while !b.is_zero() {
+            let r = &a % &b;
+            a = b;
+            b = r;
+        }
+        a
+    }
+
+    // The integer square root `floor(sqrt(self))`, by Newton's iteration `x -> (x + n/x) / 2`.
+    // Starting above the root, the iterates decrease until they reach it.
+    pub fn isqrt(&self) -> BigInt {
+        if self.is_zero() {
+            return BigInt::zero();
+        }
+        let mut x = BigInt::one() << self.bits().div_ceil(2);
+        loop {
+            let y = &(&x + &(self / &x)) >> 1;
+            if y >= x {
+                return x;
+            }
+            x = y;
+        }
+    }
+}
+
+// The operators follow the primitive integers and panic on division by zero.
+impl ops::Div<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn div(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs).expect("attempt to divide by zero").0
+    }
+}
+
+impl ops::Div for BigInt {
+    type Output = BigInt;
+    fn div(self, rhs: BigInt) -> BigInt {
+        &self / &rhs
+    }
+}
+
+impl ops::Div<&BigInt> for BigInt {
+    type Output = BigInt;
+    fn div(self, rhs: &BigInt) -> BigInt {
+        &self / rhs
+    }
+}
+
+impl ops::Div<BigInt> for &BigInt {
+    type Output = BigInt;
+    fn div(self, rhs: BigInt) -> BigInt {
+        self / &rhs
+    }
+}
+
+impl ops::Rem<&BigInt> for &BigInt {
+    type Output = BigInt;
+    fn rem(self, rhs: &BigInt) -> BigInt {
+        self.div_rem(rhs)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+impl ops::Rem for BigInt {
+    type Output = BigInt;
+    fn rem(self, rhs: BigInt) -> BigInt {
+        &self % &rhs
+    }
+}
+
+impl ops::Rem<&BigInt> for BigInt {
+    type Output = BigInt;
+    fn rem(self, rhs: &BigInt) -> BigInt {
+        &self % rhs
+    }
+}
+
+impl ops::Rem<BigInt> for &BigInt {
+    type Output = BigInt;
+    fn rem(self, rhs: BigInt) -> BigInt {
+        self % &rhs
+    }
+}
+
+// ## Decimal conversion
+
+// The largest power of ten that fits into a digit. We convert in chunks of this size.
+const DECIMAL_CHUNK: u64 = 10_000_000_000_000_000_000;
+const DECIMAL_CHUNK_DIGITS: usize = 19;
+
+// The plain conversion divides by `10^19` over and over, and each division goes over the whole
+// number. That is quadratic in the length, which is fine for numbers of a few hundred digits.
+fn to_decimal_plain(b: &BigInt) -> String {
+    let mut chunks = vec![];
+    let mut rest = b.clone();
+    while !rest.is_zero() {
+        let (q, r) = rest.div_rem_u64(DECIMAL_CHUNK);
+        chunks.push(r);
+        rest = q;
+    }
+    // The most significant chunk is printed as-is, all others are padded with zeros.
+    let mut s = match chunks.pop() {
+        None => "0".to_string(),
+        Some(top) => top.to_string(),
+    };
+    for chunk in chunks.iter().rev() {
+        s.push_str(&format!("{:019}", chunk));
+    }
+    s
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = if self.data.len() < FAST_DECIMAL_THRESHOLD {
+            to_decimal_plain(self)
+        } else {
+            to_decimal_fast(self)
+        };
+        f.pad_integral(true, "", &s)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseBigIntError {
+    Empty,
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseBigIntError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseBigIntError::Empty => write!(f, "cannot parse integer from empty string"),
+            ParseBigIntError::InvalidDigit => write!(f, "invalid digit found in string"),
+        }
+    }
+}
+
+impl BigInt {
+    // Parses a non-empty string of decimal digits. Signs are left to the callers.
+    pub fn parse_digits(s: &str) -> Result<BigInt, ParseBigIntError> {
+        if s.is_empty() {
+            return Err(ParseBigIntError::Empty);
+        }
+        if !s.bytes().all(|c| c.is_ascii_digit()) {
+            return Err(ParseBigIntError::InvalidDigit);
+        }
+        Ok(if s.len() < FAST_DECIMAL_THRESHOLD * DECIMAL_CHUNK_DIGITS {
+            parse_decimal_plain(s)
+        } else {
+            parse_decimal_fast(s)
+        })
+    }
+}
+
+// The plain parser multiplies by `10^19` and adds the next chunk, again going over the whole
+// number each time. `s` must consist of decimal digits only.
+fn parse_decimal_plain(s: &str) -> BigInt {
+    let mut result = BigInt::zero();
+    // The first chunk takes up the odd digits, so that all following chunks are full.
+    let first = s.len() % DECIMAL_CHUNK_DIGITS;
+    let mut start = 0;
+    let mut end = if first == 0 {
+        DECIMAL_CHUNK_DIGITS
+    } else {
+        first
+    };
+    while start < s.len() {
+        // All characters are ASCII digits, so slicing and parsing cannot fail.
+        let chunk: u64 = s[start..end].parse().unwrap();
+        result.mul_add_small(10u64.pow((end - start) as u32), chunk);
+        start = end;
+        end += DECIMAL_CHUNK_DIGITS;
+    }
+    result
+}
+
+// ## Fast decimal conversion
+
+// For long numbers, we convert by divide and conquer instead. To print `x`, we split it as
+// `x = q * 10^k + r` for a `k` of about half its digits, and print `q` followed by `r`, padded to
+// `k` digits. Parsing does the same in reverse. Using `k = 19 * 2^j`, the same few powers of ten are
+// needed over and over, so we compute them once, by repeated squaring. All the work is then in
+// multiplying (and dividing) numbers of similar size, and with Karatsuba and the division below,
+// that is subquadratic.
+
+// From this many digits (or `19` times as many decimal digits) on, we use the fast conversion.
+const FAST_DECIMAL_THRESHOLD: usize = 64;
+
+// The powers `10^(19 * 2^j)`, for `j = 0, 1, ...`, as long as `more` says so.
+fn decimal_powers<F: Fn(&BigInt) -> bool>(more: F) -> Vec<BigInt> {
+    let mut powers = vec![BigInt::new(DECIMAL_CHUNK)];
+    loop {
+        let last = powers.last().unwrap();
+        let square = last * last;
+        if !more(&square) {
+            return powers;
+        }
+        powers.push(square);
+    }
+}
+
+// Long division (`div_rem_digits`) is quadratic no matter how fast we multiply. But when we
+// divide by the same number many times, we can precompute its reciprocal once, and then divide with
+// two multiplications.
+struct Reciprocal {
+    d: BigInt,
+    bits: usize,
+    // `floor(2^(2 bits) / d)`
+    r: BigInt,
+}
+
+// Computes `floor(2^(2n) / d)` for `2^(n-1) <= d <= 2^n`, by Newton's iteration
+// `x -> x + x (2^(2n) - d x) / 2^(2n)`, which doubles the number of correct bits in each step.
+// Recursively, we compute the reciprocal of the top half of `d`, which is good for about half the
+// bits, and then do one step. We round `d` up before that, so all approximations stay below the
+// true value, and at the end add the few missing units.
+fn reciprocal(d: &BigInt, n: usize) -> BigInt {
+    let one_shifted = BigInt::one() << (2 * n);
+    if n < 64 * FAST_DECIMAL_THRESHOLD {
+        return &one_shifted / d;
+    }
+    let h = n / 2 + 3;
+    let top = &(d >> (n - h)) + &BigInt::one();
+    let x0 = reciprocal(&top, h) << (n - h);
+    let error = (&one_shifted - &(d * &x0)).unwrap();
+    let mut x = &x0 + &(&(&x0 * &error) >> (2 * n));
+    let mut rem = (&one_shifted - &(d * &x)).unwrap();
+    while rem >= *d {
+        x += &BigInt::one();
+        rem = (&rem - d).unwrap();
+    }
+    x
+}
+
+impl Reciprocal {
+    fn new(d: BigInt) -> Self {
+        let bits = d.bits();
+        let r = reciprocal(&d, bits);
+        Reciprocal { d, bits, r }
+    }
+
+    // Divides `x < 2^(2 bits)`, e.g., any `x < d^2`. The estimate `x r / 2^(2 bits)` is at most two
+    // below the true quotient.
+    fn div_rem(&self, x: &BigInt) -> (BigInt, BigInt) {
+        let mut q = &(x * &self.r) >> (2 * self.bits);
+        let mut rem = (x - &(&q * &self.d)).unwrap();
+        while rem >= self.d {
+            q += &BigInt::one();
+            rem = (&rem - &self.d).unwrap();
+        }
+        (q, rem)
+    }
+}
+
+fn to_decimal_fast(x: &BigInt) -> String {
+    // The largest power we need is the last one whose square exceeds `x`.
+    let powers: Vec<Reciprocal> = decimal_powers(|square| square <= x)
+        .into_iter()
+        .map(Reciprocal::new)
+        .collect();
+    let mut out = String::new();
+    write_decimal(x, &powers, powers.len() - 1, 0, &mut out);
+    out
+}
+
+// Appends `x < 10^(19 * 2^(j+1))` to `out`, padded with zeros to at least `width` digits.
+fn write_decimal(x: &BigInt, powers: &[Reciprocal], j: usize, width: usize, out: &mut String) {
+    if j == 0 || x.data.len() < FAST_DECIMAL_THRESHOLD {
+        let s = to_decimal_plain(x);
+        for _ in s.len()..width {
+            out.push('0');
+        }
+        out.push_str(&s);
+        return;
+    }
+    // On the leftmost path, a split may have nothing above it. Skipping it keeps a zero high part
+    // from showing up as a leading `0`.
+    if *x < powers[j].d {
+        return write_decimal(x, powers, j - 1, width, out);
+    }
+    let digits = DECIMAL_CHUNK_DIGITS << j;
+    let (q, r) = powers[j].div_rem(x);
+    write_decimal(&q, powers, j - 1, width.saturating_sub(digits), out);
+    write_decimal(&r, powers, j - 1, digits, out);
+}
+
+fn parse_decimal_fast(s: &str) -> BigInt {
+    let powers = decimal_powers(|square| square.bits() < 4 * s.len());
+    parse_decimal_split(s, &powers)
+}
+
+// We split off the lowest `19 * 2^j` digits, for the largest `j` that leaves something above.
+fn parse_decimal_split(s: &str, powers: &[BigInt]) -> BigInt {
+    if s.len() < FAST_DECIMAL_THRESHOLD * DECIMAL_CHUNK_DIGITS {
+        return parse_decimal_plain(s);
+    }
+    let mut j = powers.len() - 1;
+    while DECIMAL_CHUNK_DIGITS << j >= s.len() {
+        j -= 1;
+    }
+    let split = s.len() - (DECIMAL_CHUNK_DIGITS << j);
+    let high = parse_decimal_split(&s[..split], powers);
+    let low = parse_decimal_split(&s[split..], powers);
+    &(&high * &powers[j]) + &low
+}
+
+// Like the primitive unsigned integers, we accept an optional leading `+`.
+impl str::FromStr for BigInt {
+    type Err = ParseBigIntError;
+    fn from_str(s: &str) -> Result<BigInt, ParseBigIntError> {
+        BigInt::parse_digits(s.strip_prefix('+').unwrap_or(s))
+    }
+}
+
+// ## Hexadecimal conversion
+
+// Cryptographic constants are usually written in hexadecimal. Since 16 divides `2^64`, every digit
+// is exactly 16 hex digits, and no division is needed.
+impl BigInt {
+    // Parses a non-empty string of hex digits, in either case, and without a `0x` prefix.
+    pub fn parse_hex(s: &str) -> Result<BigInt, ParseBigIntError> {
+        if s.is_empty() {
+            return Err(ParseBigIntError::Empty);
+        }
+        if !s.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseBigIntError::InvalidDigit);
+        }
+        let data = s
+            .as_bytes()
+            .rchunks(16)
+            .map(|chunk| u64::from_str_radix(str::from_utf8(chunk).unwrap(), 16).unwrap())
+            .collect();
+        Ok(BigInt::from_vec(data))
+    }
+
+    fn to_hex(&self, upper: bool) -> String {
+        let mut s = match self.data.last() {
+            None => "0".to_string(),
+            Some(top) => format!("{:x}", top),
+        };
+        for d in self.data.iter().rev().skip(1) {
+            s.push_str(&format!("{:016x}", d));
+        }
+        if upper {
+            s.make_ascii_uppercase();
+        }
+        s
+    }
+}
+
+// As for the primitive types, `{:#x}` adds a `0x` prefix.
+impl fmt::LowerHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex(false))
+    }
+}
+
+impl fmt::UpperHex for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_hex(true))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use part05::BigInt;
+
+    fn big(s: &str) -> BigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_cmp() {
+        let b1 = BigInt::from_vec(vec![5, 1]);
+        let b2 = BigInt::from_vec(vec![7, 1]);
+        let b3 = BigInt::new(u64::MAX);
+        assert!(b1 < b2);
+        assert!(b3 < b1);
+        assert_eq!(b1.cmp(&b1.clone()), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_decimal() {
+        let s = "340282366920938463463374607431768211456";
+        assert_eq!(big(s), BigInt::from_vec(vec![0, 0, 1]));
+        assert_eq!(big(s).to_string(), s);
+        assert_eq!(big("+0").to_string(), "0");
+        assert_eq!(
+            big("10000000000000000000").to_string(),
+            "10000000000000000000"
+        );
+        assert_eq!("".parse::<BigInt>(), Err(ParseBigIntError::Empty));
+        assert_eq!("12a".parse::<BigInt>(), Err(ParseBigIntError::InvalidDigit));
+        assert_eq!(format!("{:>5}", BigInt::new(42)), "   42");
+    }
+
+    #[test]
+    fn test_mul_div() {
+        let a = big("123456789012345678901234567890123456789");
+        let b = big("987654321098765432109876543210");
+        let p = &a * &b;
+        assert_eq!(
+            p.to_string(),
+            "121932631137021795226185032733744855963362292333223746380111126352690"
+        );
+        assert_eq!(&p / &b, a);
+        assert_eq!(&(&p + BigInt::new(17)) % &b, BigInt::new(17));
+        assert_eq!(a.div_rem(&BigInt::zero()), None);
+        assert_eq!(
+            BigInt::new(2).pow(128),
+            big("340282366920938463463374607431768211456")
+        );
+        // A divisor whose top digit has its high bit set needs no normalization.
+        let d = BigInt::from_vec(vec![3, 1 << 63]);
+        let (q, r) = p.div_rem(&d).unwrap();
+        assert_eq!(&(&q * &d) + &r, p);
+        assert!(r < d);
+    }
+
+    #[test]
+    fn test_assign() {
+        let mut a = BigInt::from_vec(vec![u64::MAX, u64::MAX]);
+        a += &BigInt::new(1);
+        assert_eq!(a, BigInt::from_vec(vec![0, 0, 1]));
+        let mut b = BigInt::new(5);
+        b += BigInt::from_vec(vec![1, 2]);
+        assert_eq!(b, BigInt::from_vec(vec![6, 2]));
+        b *= BigInt::zero();
+        assert!(b.is_zero() && b.test_invariant());
+    }
+
+    #[test]
+    fn test_shift_gcd() {
+        let a = big("1000000000000000000000000");
+        assert_eq!(&(&a << 100) >> 100, a);
+        assert_eq!(&a >> 200, BigInt::zero());
+        assert_eq!(big("462").gcd(&big("1071")), BigInt::new(21));
+        assert_eq!(a.bits(), 80);
+        assert!(a.bit(79) && !a.bit(80));
+        assert_eq!(a.isqrt(), big("1000000000000"));
+        assert_eq!((&a - &BigInt::one()).unwrap().isqrt(), big("999999999999"));
+        assert_eq!(BigInt::new(15).isqrt(), BigInt::new(3));
+    }
+
+    // Digits from a simple linear congruential generator, so that there are no patterns the fast
+    // algorithms could get right by accident.
+    fn pseudo_random(digits: usize, seed: u64) -> BigInt {
+        let mut x = seed;
+        let data = (0..digits)
+            .map(|_| {
+                x = x
+                    .wrapping_mul(6_364_136_223_846_793_005)
+                    .wrapping_add(1_442_695_040_888_963_407);
+                x
+            })
+            .collect();
+        BigInt::from_vec(data)
+    }
+
+    #[test]
+    fn test_fast_algorithms() {
+        let (a, b) = (pseudo_random(300, 1), pseudo_random(170, 2));
+        assert_eq!(
+            karatsuba(&a, &b),
+            BigInt::from_vec(mul_digits(&a.data, &b.data))
+        );
+        // Large enough for `reciprocal` to recurse, and then to divide numbers of twice the size.
+        let d = pseudo_random(150, 3);
+        let reciprocal = Reciprocal::new(d.clone());
+        for x in [&(&d * &d) - &BigInt::one(), Some(pseudo_random(200, 4))] {
+            let x = x.unwrap();
+            assert_eq!(Some(reciprocal.div_rem(&x)), x.div_rem(&d));
+        }
+        // Several thousand digits, with zeros in the middle that the padding has to get right.
+        let x = &(&a << 5000) + &b;
+        let s = to_decimal_plain(&x);
+        assert!(s.len() > FAST_DECIMAL_THRESHOLD * DECIMAL_CHUNK_DIGITS);
+        assert_eq!(to_decimal_fast(&x), s);
+        assert_eq!(parse_decimal_fast(&s), x);
+        assert_eq!(parse_decimal_plain(&s), x);
+        let power = BigInt::new(10).pow(5000);
+        assert_eq!(power.to_string().len(), 5001);
+        assert_eq!(big(&power.to_string()), power);
+    }
+
+    #[test]
+    fn test_bytes() {
+        let bytes = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let b = BigInt::from_bytes_be(&bytes);
+        assert_eq!(b, BigInt::from_vec(vec![0x0203040506070809, 1]));
+        assert_eq!(b.to_bytes_be(), bytes.to_vec());
+        assert_eq!(BigInt::from_bytes_be(&[0, 0, 7]).to_bytes_be(), vec![7]);
+        assert_eq!(BigInt::zero().to_bytes_be(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn test_hex() {
+        let b = BigInt::parse_hex("1fffffffffffffffF").unwrap();
+        assert_eq!(b, BigInt::from_vec(vec![u64::MAX, 1]));
+        assert_eq!(format!("{:x}", b), "1ffffffffffffffff");
+        assert_eq!(
+            format!("{:#X}", BigInt::from_vec(vec![0, 10])),
+            "0xA0000000000000000"
+        );
+        assert_eq!(format!("{:x}", BigInt::zero()), "0");
+        assert_eq!(
+            BigInt::parse_hex("0x1"),
+            Err(ParseBigIntError::InvalidDigit)
+        );
+    }
+}
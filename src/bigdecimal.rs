@@ -0,0 +1,340 @@
+// Arbitrary-Precision Decimals
+// ============================
+
+// Binary floating point cannot represent `0.1` exactly, which is a problem as soon as money is
+// involved. A `BigDecimal` instead stores an integer mantissa together with a decimal scale: the
+// value is `mantissa / 10^scale`. So `-12.3400` is the mantissa `-123400` at scale 4.
+use arith::ParseBigIntError;
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::cmp::Ordering;
+use std::{fmt, ops, str};
+
+#[derive(Clone)]
+pub struct BigDecimal {
+    mantissa: SignedBigInt,
+    scale: u32,
+}
+
+// Addition, subtraction and multiplication are exact. Division and rescaling are not, so they
+// take one of these modes to decide which way to round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    // Round to the nearest neighbor, and ties to the even one ("banker's rounding").
+    HalfEven,
+    // Round to the nearest neighbor, and ties away from zero (as taught in school).
+    HalfUp,
+    // Round towards negative infinity.
+    Floor,
+    // Round towards positive infinity.
+    Ceiling,
+    // Round towards zero, i.e., drop the extra digits.
+    Truncate,
+}
+
+fn pow10(n: u32) -> BigInt {
+    BigInt::new(10).pow(n)
+}
+
+// Computes `num / den`, rounded to an integer according to `mode`. The denominator must not be
+// zero.
+fn div_round(num: &SignedBigInt, den: &SignedBigInt, mode: RoundingMode) -> SignedBigInt {
+    let (q, r) = num.div_rem(den).expect("attempt to divide by zero");
+    if r.is_zero() {
+        return q;
+    }
+    // `q` was rounded towards zero. If we have to round the other way, we step away from zero in
+    // the direction of the exact result.
+    let negative = num.is_negative() != den.is_negative();
+    let away = match mode {
+        RoundingMode::Truncate => false,
+        RoundingMode::Floor => negative,
+        RoundingMode::Ceiling => !negative,
+        RoundingMode::HalfUp | RoundingMode::HalfEven => {
+            // Compare the remainder against half of the denominator.
+            let twice_r = r.magnitude() << 1;
+            match twice_r.cmp(den.magnitude()) {
+                Ordering::Less => false,
+                Ordering::Greater => true,
+                Ordering::Equal => mode == RoundingMode::HalfUp || !q.is_even(),
+            }
+        }
+    };
+    if !away {
+        q
+    } else if negative {
+        q - SignedBigInt::one()
+    } else {
+        q + SignedBigInt::one()
+    }
+}
+
+impl BigDecimal {
+    pub fn new(mantissa: SignedBigInt, scale: u32) -> Self {
+        BigDecimal { mantissa, scale }
+    }
+
+    pub fn zero() -> Self {
+        BigDecimal::new(SignedBigInt::zero(), 0)
+    }
+
+    pub fn mantissa(&self) -> &SignedBigInt {
+        &self.mantissa
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.mantissa.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.mantissa.is_negative()
+    }
+
+    pub fn abs(&self) -> BigDecimal {
+        BigDecimal::new(self.mantissa.abs(), self.scale)
+    }
+
+    // Returns the same value with `scale` fractional digits, rounding if digits have to be
+    // dropped.
+    pub fn round(&self, scale: u32, mode: RoundingMode) -> BigDecimal {
+        if scale >= self.scale {
+            let factor = SignedBigInt::from(pow10(scale - self.scale));
+            BigDecimal::new(&self.mantissa * &factor, scale)
+        } else {
+            let factor = SignedBigInt::from(pow10(self.scale - scale));
+            BigDecimal::new(div_round(&self.mantissa, &factor, mode), scale)
+        }
+    }
+
+    // Removes trailing zeros from the fractional part, so `1.500` becomes `1.5` and `2.0` becomes
+    // `2`. This is the shortest representation of the value.
+    pub fn normalize(&self) -> BigDecimal {
+        let mut mantissa = self.mantissa.magnitude().clone();
+        let mut scale = self.scale;
+        while scale > 0 {
+            let (q, r) = mantissa.div_rem_u64(10);
+            if r != 0 {
+                break;
+            }
+            mantissa = q;
+            scale -= 1;
+        }
+        BigDecimal::new(SignedBigInt::new(self.is_negative(), mantissa), scale)
+    }
+
+    // Brings both numbers to the larger of the two scales, which is exact.
+    fn aligned(&self, other: &BigDecimal) -> (SignedBigInt, SignedBigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        let a = self.round(scale, RoundingMode::Truncate);
+        let b = other.round(scale, RoundingMode::Truncate);
+        (a.mantissa, b.mantissa, scale)
+    }
+
+    // Divides `self` by `divisor`, keeping `scale` fractional digits in the result. Returns `None`
+    // for a zero divisor.
+    pub fn div(&self, divisor: &BigDecimal, scale: u32, mode: RoundingMode) -> Option<BigDecimal> {
+        if divisor.is_zero() {
+            return None;
+        }
+        // We want `q = (a / 10^sa) / (b / 10^sb) * 10^scale`, i.e., `q = a * 10^(scale + sb) /
+        // (b * 10^sa)`. Both sides are integers, so a single rounded integer division does it.
+        let num = &self.mantissa * &SignedBigInt::from(pow10(scale + divisor.scale));
+        let den = &divisor.mantissa * &SignedBigInt::from(pow10(self.scale));
+        Some(BigDecimal::new(div_round(&num, &den, mode), scale))
+    }
+}
+
+impl From<SignedBigInt> for BigDecimal {
+    fn from(mantissa: SignedBigInt) -> Self {
+        BigDecimal::new(mantissa, 0)
+    }
+}
+
+impl From<i64> for BigDecimal {
+    fn from(x: i64) -> Self {
+        BigDecimal::from(SignedBigInt::from(x))
+    }
+}
+
+// ## Comparison
+
+// Comparison goes by value, so `1.5 == 1.50` even though they print differently.
+impl PartialEq for BigDecimal {
+    fn eq(&self, other: &BigDecimal) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigDecimal {}
+
+impl Ord for BigDecimal {
+    fn cmp(&self, other: &BigDecimal) -> Ordering {
+        let (a, b, _) = self.aligned(other);
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for BigDecimal {
+    fn partial_cmp(&self, other: &BigDecimal) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ## Arithmetic
+
+impl ops::Add<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = self.aligned(rhs);
+        BigDecimal::new(a + b, scale)
+    }
+}
+
+impl ops::Sub<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: &BigDecimal) -> BigDecimal {
+        let (a, b, scale) = self.aligned(rhs);
+        BigDecimal::new(a - b, scale)
+    }
+}
+
+// The product of two decimals with `m` and `n` fractional digits has exactly `m + n` of them.
+impl ops::Mul<&BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: &BigDecimal) -> BigDecimal {
+        BigDecimal::new(&self.mantissa * &rhs.mantissa, self.scale + rhs.scale)
+    }
+}
+
+forward_binop!(BigDecimal, Add, add);
+forward_binop!(BigDecimal, Sub, sub);
+forward_binop!(BigDecimal, Mul, mul);
+
+impl ops::Neg for BigDecimal {
+    type Output = BigDecimal;
+    fn neg(self) -> BigDecimal {
+        BigDecimal::new(-self.mantissa, self.scale)
+    }
+}
+
+// ## Formatting and parsing
+
+// We print the plain decimal notation with exactly `scale` fractional digits: `-0.050` stays
+// `-0.050`, and there is never an exponent. Use `normalize` first to get the shortest form.
+impl fmt::Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits = self.mantissa.magnitude().to_string();
+        let scale = self.scale as usize;
+        if scale > 0 {
+            // Make sure there is at least one digit before the decimal point.
+            if digits.len() <= scale {
+                digits = "0".repeat(scale + 1 - digits.len()) + &digits;
+            }
+            digits.insert(digits.len() - scale, '.');
+        }
+        f.pad_integral(!self.is_negative(), "", &digits)
+    }
+}
+
+impl fmt::Debug for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// Parses an optional sign followed by digits with an optional decimal point, like `-12.3400`,
+// `.5` or `7.`. The number of fractional digits becomes the scale.
+impl str::FromStr for BigDecimal {
+    type Err = ParseBigIntError;
+    fn from_str(s: &str) -> Result<BigDecimal, ParseBigIntError> {
+        let (negative, rest) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        let (int_part, frac_part) = match rest.find('.') {
+            Some(i) => (&rest[..i], &rest[i + 1..]),
+            None => (rest, ""),
+        };
+        let digits = format!("{}{}", int_part, frac_part);
+        let mantissa = BigInt::parse_digits(&digits)?;
+        Ok(BigDecimal::new(
+            SignedBigInt::new(negative, mantissa),
+            frac_part.len() as u32,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(s: &str) -> BigDecimal {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(dec("-12.3400").to_string(), "-12.3400");
+        assert_eq!(dec("-12.3400").scale(), 4);
+        assert_eq!(dec("-12.3400").normalize().to_string(), "-12.34");
+        assert_eq!(dec(".05").to_string(), "0.05");
+        assert_eq!(dec("-0.0").to_string(), "0.0");
+        assert_eq!(dec("100").normalize().to_string(), "100");
+        assert_eq!(dec("1.5"), dec("1.500"));
+        assert!("1.2.3".parse::<BigDecimal>().is_err());
+        assert!(".".parse::<BigDecimal>().is_err());
+    }
+
+    #[test]
+    fn test_arith() {
+        // The classic: with binary floats, this is `0.30000000000000004`.
+        assert_eq!((dec("0.1") + dec("0.2")).to_string(), "0.3");
+        assert_eq!((dec("1.05") - dec("2")).to_string(), "-0.95");
+        assert_eq!((dec("-1.5") * dec("0.25")).to_string(), "-0.375");
+        assert!(dec("-0.001") < dec("0"));
+    }
+
+    #[test]
+    fn test_div_rounding() {
+        use self::RoundingMode::*;
+        let one = dec("1");
+        let third = one.div(&dec("3"), 4, HalfEven).unwrap();
+        assert_eq!(third.to_string(), "0.3333");
+        assert_eq!(
+            dec("2").div(&dec("3"), 2, HalfUp).unwrap().to_string(),
+            "0.67"
+        );
+        assert_eq!(
+            dec("2").div(&dec("3"), 2, Truncate).unwrap().to_string(),
+            "0.66"
+        );
+        assert_eq!(
+            dec("-2").div(&dec("3"), 2, Floor).unwrap().to_string(),
+            "-0.67"
+        );
+        assert_eq!(
+            dec("-2").div(&dec("3"), 2, Ceiling).unwrap().to_string(),
+            "-0.66"
+        );
+        assert_eq!(
+            dec("10")
+                .div(&dec("0.04"), 0, Truncate)
+                .unwrap()
+                .to_string(),
+            "250"
+        );
+        assert_eq!(one.div(&dec("0.0"), 2, HalfUp), None);
+
+        // Ties are where the two "half" modes differ.
+        let cases = [("2.5", "3", "2"), ("3.5", "4", "4"), ("-2.5", "-3", "-2")];
+        for &(x, half_up, half_even) in cases.iter() {
+            assert_eq!(dec(x).round(0, HalfUp).to_string(), half_up);
+            assert_eq!(dec(x).round(0, HalfEven).to_string(), half_even);
+        }
+        assert_eq!(dec("1.2").round(3, HalfEven).to_string(), "1.200");
+    }
+}
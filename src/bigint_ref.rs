@@ -0,0 +1,187 @@
+// Borrowed BigInt Views
+// =====================
+
+// `BigInt` owns its digits in a `Vec`. Algorithms like Karatsuba multiplication split a number
+// into its lower and upper half, and it would be wasteful to copy the halves into new `BigInt`s
+// just to look at them. What we want is to `BigInt` what `str` is to `String`: a borrowed view
+// onto some digits.
+use part05::BigInt;
+use part07::Minimum;
+use std::borrow::{Borrow, ToOwned};
+use std::cmp::Ordering;
+use std::{fmt, ops};
+
+// Like `str`, this is an *unsized* type: it is just a slice of digits with a different name, so we
+// can only ever handle it behind a reference. `repr(transparent)` guarantees that it is laid out
+// exactly like `[u64]`, which makes the cast in `new` sound.
+//
+// Unlike `BigInt`, a view may have trailing zeros, because the lower half of a split number
+// usually does. All operations treat them as insignificant.
+#[repr(transparent)]
+pub struct BigIntRef {
+    data: [u64],
+}
+
+impl BigIntRef {
+    pub fn new(digits: &[u64]) -> &BigIntRef {
+        // This is just a pointer cast from `&[u64]` to a type with the same layout.
+        unsafe { &*(digits as *const [u64] as *const BigIntRef) }
+    }
+
+    // The digits, least significant first, including any trailing zeros.
+    pub fn digits(&self) -> &[u64] {
+        &self.data
+    }
+
+    // The same number without trailing zeros.
+    pub fn trimmed(&self) -> &BigIntRef {
+        let len = self
+            .data
+            .iter()
+            .rposition(|&d| d != 0)
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        BigIntRef::new(&self.data[..len])
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.data.iter().all(|&d| d == 0)
+    }
+
+    // Splits into the number formed by the lowest `k` digits, and the one formed by the rest, i.e.,
+    // `self = low + high * 2^(64k)`. Neither half is copied.
+    pub fn split_at(&self, k: usize) -> (&BigIntRef, &BigIntRef) {
+        let k = k.min(self.data.len());
+        let (low, high) = self.data.split_at(k);
+        (BigIntRef::new(low), BigIntRef::new(high))
+    }
+
+    // Writes `self + other` into `out`, replacing its contents. Reusing the same buffer across many
+    // additions saves the allocation that `Add` on `BigInt` has to do.
+    pub fn add_into(&self, other: &BigIntRef, out: &mut Vec<u64>) {
+        let (a, b) = (&self.trimmed().data, &other.trimmed().data);
+        out.clear();
+        let mut carry = false;
+        for i in 0..a.len().max(b.len()) {
+            let x = a.get(i).cloned().unwrap_or(0);
+            let y = b.get(i).cloned().unwrap_or(0);
+            let (sum, c1) = x.overflowing_add(y);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            out.push(sum);
+            carry = c1 || c2;
+        }
+        if carry {
+            out.push(1);
+        }
+    }
+}
+
+// ## Conversions
+
+// This is the central piece: every `BigInt` can be used where a `&BigIntRef` is expected, and all
+// methods of `BigIntRef` are available on `BigInt`.
+impl ops::Deref for BigInt {
+    type Target = BigIntRef;
+    fn deref(&self) -> &BigIntRef {
+        BigIntRef::new(&self.data)
+    }
+}
+
+impl Borrow<BigIntRef> for BigInt {
+    fn borrow(&self) -> &BigIntRef {
+        self
+    }
+}
+
+// Going back to an owned number copies the digits. `from_vec` removes the trailing zeros.
+impl ToOwned for BigIntRef {
+    type Owned = BigInt;
+    fn to_owned(&self) -> BigInt {
+        BigInt::from_vec(self.data.to_vec())
+    }
+}
+
+// ## Comparison
+
+impl PartialEq for BigIntRef {
+    fn eq(&self, other: &BigIntRef) -> bool {
+        self.trimmed().data == other.trimmed().data
+    }
+}
+
+impl Eq for BigIntRef {}
+
+// As for `BigInt` (see `arith`), we give `cmp` as an inherent method instead of implementing
+// `Ord`, whose `min` would clash with `Minimum::min` on references.
+impl BigIntRef {
+    pub fn cmp(&self, other: &BigIntRef) -> Ordering {
+        let (a, b) = (&self.trimmed().data, &other.trimmed().data);
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigIntRef {
+    fn partial_cmp(&self, other: &BigIntRef) -> Option<Ordering> {
+        Some(BigIntRef::cmp(self, other))
+    }
+}
+
+// Part 07's `Minimum` hands out borrowed results, so it works for views without any copying,
+// even though they are unsized.
+impl Minimum for BigIntRef {
+    fn min<'a>(&'a self, other: &'a Self) -> &'a Self {
+        if self <= other {
+            self
+        } else {
+            other
+        }
+    }
+}
+
+// ## Formatting
+
+// Printing in decimal needs repeated division, so we have to work on a copy anyway.
+impl fmt::Display for BigIntRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.to_owned(), f)
+    }
+}
+
+impl fmt::Debug for BigIntRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.trimmed().data.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view() {
+        let b = BigInt::from_vec(vec![5, 0, 7]);
+        let (low, high) = b.split_at(2);
+        assert_eq!(low.digits(), &[5, 0]);
+        assert_eq!(*low, *BigIntRef::new(&[5]));
+        assert_eq!(high.to_owned(), BigInt::new(7));
+        assert!(low < high);
+        assert_eq!(high.min(low), low);
+        assert_eq!(low.to_string(), "5");
+        assert_eq!(format!("{:?}", low), "[5]");
+        assert!(BigIntRef::new(&[0, 0]).is_zero());
+    }
+
+    #[test]
+    fn test_add_into() {
+        let a = BigInt::from_vec(vec![u64::MAX, u64::MAX]);
+        let b = BigInt::new(1);
+        let mut out = vec![];
+        a.add_into(&b, &mut out);
+        assert_eq!(out, vec![0, 0, 1]);
+        // The same buffer can be reused, and trailing zeros of the inputs are ignored.
+        BigIntRef::new(&[3, 0, 0]).add_into(BigIntRef::new(&[4]), &mut out);
+        assert_eq!(out, vec![7]);
+    }
+}
@@ -0,0 +1,194 @@
+// Digits of Mathematical Constants
+// ================================
+
+// Computing many digits of pi is the classic workout for big integer arithmetic: thousands of
+// multiplications and divisions of numbers with thousands of digits, and one wrong carry shows up
+// as wrong digits. We compute each constant `c` as the integer `floor(c * 10^n)`, whose decimal
+// digits are the first digits of `c`.
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::fmt::Write;
+
+fn pow10(n: usize) -> BigInt {
+    BigInt::new(10).pow(n as u32)
+}
+
+// We compute a few more digits than asked for, so that the rounding errors of the last steps do not
+// reach the digits we return. (Unless the constant happens to continue with a long run of 9s.)
+const GUARD_DIGITS: usize = 10;
+
+// ## Binary splitting
+
+// Series like `sum a(k)/b(k)` with small rational terms can be summed exactly as one big fraction.
+// Doing that term by term makes the numbers grow with every step, so every step is expensive.
+// *Binary splitting* instead sums the two halves of the range recursively, and combines them with
+// a few multiplications. Most of the work then happens on numbers of similar size, which is what
+// fast multiplication likes.
+
+// The Chudnovsky series:
+// `1/pi = 12 * sum (-1)^k (6k)! (13591409 + 545140134 k) / ((3k)! (k!)^3 640320^(3k + 3/2))`.
+// Each term contributes about 14 digits. For the range `a..b`, we return `P`, `Q` and `T`, where
+// `P/Q` is the ratio of the terms at `b` and `a`, and `T/Q` the sum, scaled by the term at `a`.
+fn chudnovsky(a: u64, b: u64) -> (BigInt, BigInt, SignedBigInt) {
+    if b == a + 1 {
+        if a == 0 {
+            return (BigInt::one(), BigInt::one(), SignedBigInt::from(13_591_409));
+        }
+        // `640320^3 / 24`
+        const C3_OVER_24: u64 = 10_939_058_860_032_000;
+        let p = &(&BigInt::new(6 * a - 5) * &BigInt::new(2 * a - 1)) * &BigInt::new(6 * a - 1);
+        let q = &BigInt::new(a).pow(3) * &BigInt::new(C3_OVER_24);
+        let t = &p * &(&BigInt::new(545_140_134) * &BigInt::new(a) + BigInt::new(13_591_409));
+        return (p, q, SignedBigInt::new(a % 2 == 1, t));
+    }
+    let m = (a + b) / 2;
+    let (p1, q1, t1) = chudnovsky(a, m);
+    let (p2, q2, t2) = chudnovsky(m, b);
+    let t = &t1 * &SignedBigInt::from(q2.clone()) + &SignedBigInt::from(p1.clone()) * &t2;
+    (&p1 * &p2, &q1 * &q2, t)
+}
+
+// `floor(pi * 10^digits)`. Summing the series up to `k`, we get
+// `pi = 426880 * sqrt(10005) * Q / T`.
+pub fn pi(digits: usize) -> BigInt {
+    let precision = digits + GUARD_DIGITS;
+    let terms = (precision / 14 + 2) as u64;
+    let (_, q, t) = chudnovsky(0, terms);
+    let sqrt_10005 = (&BigInt::new(10005) * &pow10(2 * precision)).isqrt();
+    let numerator = &(&q * &sqrt_10005) * &BigInt::new(426_880);
+    // The first term dominates, so `T` is positive.
+    let pi = &numerator / &t.to_bigint().unwrap();
+    &pi / &pow10(GUARD_DIGITS)
+}
+
+// For `e = sum 1/k!`, we return `p` and `q = (a+1) (a+2) ... b` with
+// `p/q = sum_{k = a+1..=b} a!/k!`. Then `e = 1 + p/q` for the range starting at 0.
+fn e_series(a: u64, b: u64) -> (BigInt, BigInt) {
+    if b == a + 1 {
+        return (BigInt::one(), BigInt::new(b));
+    }
+    let m = (a + b) / 2;
+    let (p1, q1) = e_series(a, m);
+    let (p2, q2) = e_series(m, b);
+    (&(&p1 * &q2) + &p2, &q1 * &q2)
+}
+
+// `floor(e * 10^digits)`. We need `k` terms with `k! > 10^precision`, so that the rest of the
+// series does not matter.
+pub fn e(digits: usize) -> BigInt {
+    let precision = digits + GUARD_DIGITS;
+    let mut terms = 1;
+    let mut log10_factorial = 0.0;
+    while log10_factorial <= precision as f64 {
+        terms += 1;
+        log10_factorial += (terms as f64).log10();
+    }
+    let (p, q) = e_series(0, terms);
+    let e = &(&pow10(precision) * &(&q + &p)) / &q;
+    &e / &pow10(GUARD_DIGITS)
+}
+
+// `floor(sqrt(n) * 10^digits)`. Here, the integer square root is exact, so we need no guard digits.
+pub fn sqrt(n: &BigInt, digits: usize) -> BigInt {
+    (n * &pow10(2 * digits)).isqrt()
+}
+
+// ## Streaming digits
+
+// An endless iterator over the decimal digits of a constant, starting with its integer part. When
+// we run out, we compute the constant again, with twice the precision, and continue where we
+// stopped.
+pub struct Digits {
+    // Computes `floor(c * 10^n)` for the given `n`.
+    compute: Box<dyn Fn(usize) -> BigInt>,
+    precision: usize,
+    digits: Vec<u8>,
+    next: usize,
+}
+
+impl Digits {
+    fn new(compute: Box<dyn Fn(usize) -> BigInt>) -> Self {
+        Digits {
+            compute,
+            precision: 0,
+            digits: vec![],
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for Digits {
+    type Item = u8;
+    fn next(&mut self) -> Option<u8> {
+        if self.next == self.digits.len() {
+            self.precision = (2 * self.precision).max(64);
+            // Padding with zeros makes constants below 1 come out right, too.
+            let mut s = String::new();
+            write!(
+                s,
+                "{:01$}",
+                (self.compute)(self.precision),
+                self.precision + 1
+            )
+            .unwrap();
+            self.digits = s.bytes().map(|b| b - b'0').collect();
+        }
+        self.next += 1;
+        Some(self.digits[self.next - 1])
+    }
+}
+
+pub fn pi_digits() -> Digits {
+    Digits::new(Box::new(pi))
+}
+
+pub fn e_digits() -> Digits {
+    Digits::new(Box::new(e))
+}
+
+pub fn sqrt_digits(n: &BigInt) -> Digits {
+    let n = n.clone();
+    Digits::new(Box::new(move |digits| sqrt(&n, digits)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(digits: Digits, n: usize) -> String {
+        digits.take(n).map(|d| (b'0' + d) as char).collect()
+    }
+
+    #[test]
+    fn test_first_digits() {
+        assert_eq!(
+            string(pi_digits(), 50),
+            "31415926535897932384626433832795028841971693993751"
+        );
+        assert_eq!(
+            string(e_digits(), 40),
+            "2718281828459045235360287471352662497757"
+        );
+        assert_eq!(
+            string(sqrt_digits(&BigInt::new(2)), 40),
+            "1414213562373095048801688724209698078569"
+        );
+        assert_eq!(string(sqrt_digits(&BigInt::zero()), 3), "000");
+        // Crossing the point where the iterator recomputes with more digits.
+        assert_eq!(string(pi_digits(), 201), pi(200).to_string());
+    }
+
+    // The last digits of the first thousand decimals, from an independent computation with
+    // Machin's formula.
+    #[test]
+    fn test_thousand_digits() {
+        let last = |b: BigInt| {
+            let s = b.to_string();
+            assert_eq!(s.len(), 1001);
+            s[981..].to_string()
+        };
+        assert_eq!(last(pi(1000)), "66111959092164201989");
+        assert_eq!(last(e(1000)), "12671546889570350354");
+        assert_eq!(last(sqrt(&BigInt::new(2), 1000)), "82152128229518488472");
+    }
+}
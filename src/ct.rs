@@ -0,0 +1,267 @@
+// Constant-Time Fixed-Width Arithmetic
+// ====================================
+
+// The addition in part 08 branches on the carry (`if sum >= a`, `if carry`), and `BigInt` drops
+// leading zeros, so its length gives away the size of the number. For everyday code, that is what
+// makes it fast. For cryptographic secrets, it is a leak: an attacker who can time our code learns
+// something about the secret bits.
+//
+// This module is the slow, careful counterpart. Numbers have a fixed number of digits, chosen at
+// compile time, and all functions run the same instructions and touch the same memory no matter
+// what the values are. Instead of branching on a condition, we turn it into a mask (all zeros or
+// all ones) and combine both possible results with it. Converting from and to `BigInt` is
+// variable-time, so only do that with public values, or at the boundaries of a computation.
+use part05::BigInt;
+use std::hint::black_box;
+
+// ## Choices
+
+// A secret boolean, stored as a mask: `0` for false, `!0` for true. There is deliberately no way to
+// branch on it other than `declassify`.
+#[derive(Clone, Copy, Debug)]
+pub struct Choice(u64);
+
+impl Choice {
+    // Builds a choice from a bit that has to be `0` or `1`. The `black_box` keeps the optimizer
+    // from seeing that the result is only ever one of two values, and reintroducing a branch.
+    pub fn from_bit(bit: u64) -> Choice {
+        debug_assert!(bit <= 1);
+        Choice(black_box(bit).wrapping_neg())
+    }
+
+    pub fn and(self, other: Choice) -> Choice {
+        Choice(self.0 & other.0)
+    }
+
+    pub fn or(self, other: Choice) -> Choice {
+        Choice(self.0 | other.0)
+    }
+
+    pub fn not(self) -> Choice {
+        Choice(!self.0)
+    }
+
+    // Turns the choice into a `bool`, which the caller can branch on. Only do that once the value
+    // is no longer secret, e.g., for the final result of a signature check.
+    pub fn declassify(self) -> bool {
+        self.0 != 0
+    }
+}
+
+// A word is zero iff neither it nor its negation have the top bit set.
+fn is_zero_word(x: u64) -> Choice {
+    Choice::from_bit(((x | x.wrapping_neg()) >> 63) ^ 1)
+}
+
+// Picks `b` if `choice` is true, and `a` otherwise.
+fn select_word(a: u64, b: u64, choice: Choice) -> u64 {
+    a ^ (choice.0 & (a ^ b))
+}
+
+// Addition with carry, without any comparison: the `u128` sum holds the carry in its upper half.
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+// Subtraction with borrow. The wrapped-around upper half is all ones iff we had to borrow.
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, ((t >> 64) as u64) & 1)
+}
+
+// ## Fixed-width numbers
+
+// An unsigned number of exactly `N` digits, least significant first, as in `BigInt`. Unlike
+// `BigInt`, leading zero digits are kept, so all numbers of one type look alike. There is no `==`,
+// since a derived one stops at the first differing digit; use `ct_eq`.
+#[derive(Clone, Copy, Debug)]
+pub struct CtUint<const N: usize> {
+    data: [u64; N],
+}
+
+impl<const N: usize> CtUint<N> {
+    pub fn zero() -> Self {
+        CtUint { data: [0; N] }
+    }
+
+    pub fn one() -> Self {
+        let mut result = Self::zero();
+        result.data[0] = 1;
+        result
+    }
+
+    // Returns `None` if the number needs more than `N` digits.
+    pub fn from_bigint(b: &BigInt) -> Option<Self> {
+        if b.data.len() > N {
+            return None;
+        }
+        let mut result = Self::zero();
+        result.data[..b.data.len()].copy_from_slice(&b.data);
+        Some(result)
+    }
+
+    pub fn to_bigint(self) -> BigInt {
+        BigInt::from_vec(self.data.to_vec())
+    }
+
+    pub fn digits(&self) -> &[u64; N] {
+        &self.data
+    }
+
+    // ## Selection
+
+    // Returns `b` if `choice` is true, and `a` otherwise, looking at both in full.
+    pub fn select(a: &Self, b: &Self, choice: Choice) -> Self {
+        let mut result = *a;
+        for i in 0..N {
+            result.data[i] = select_word(a.data[i], b.data[i], choice);
+        }
+        result
+    }
+
+    // Swaps `a` and `b` if `choice` is true. This is what a constant-time Montgomery ladder uses
+    // in place of the `mem::swap` in `ec::Curve::mul`.
+    pub fn swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        for i in 0..N {
+            let t = choice.0 & (a.data[i] ^ b.data[i]);
+            a.data[i] ^= t;
+            b.data[i] ^= t;
+        }
+    }
+
+    // ## Comparison
+
+    // We accumulate the differences of all digits, instead of stopping at the first one.
+    pub fn ct_eq(&self, other: &Self) -> Choice {
+        let diff = (0..N).fold(0, |acc, i| acc | (self.data[i] ^ other.data[i]));
+        is_zero_word(diff)
+    }
+
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&Self::zero())
+    }
+
+    // `self < other` iff computing `self - other` borrows.
+    pub fn ct_lt(&self, other: &Self) -> Choice {
+        Choice::from_bit(self.overflowing_sub(other).1)
+    }
+
+    // ## Arithmetic
+
+    // Returns the sum modulo `2^(64N)`, and the carry out of the top digit (`0` or `1`).
+    pub fn overflowing_add(&self, other: &Self) -> (Self, u64) {
+        let mut result = Self::zero();
+        let mut carry = 0;
+        for i in 0..N {
+            let (d, c) = adc(self.data[i], other.data[i], carry);
+            result.data[i] = d;
+            carry = c;
+        }
+        (result, carry)
+    }
+
+    // Returns the difference modulo `2^(64N)`, and whether we had to borrow (`0` or `1`).
+    pub fn overflowing_sub(&self, other: &Self) -> (Self, u64) {
+        let mut result = Self::zero();
+        let mut borrow = 0;
+        for i in 0..N {
+            let (d, b) = sbb(self.data[i], other.data[i], borrow);
+            result.data[i] = d;
+            borrow = b;
+        }
+        (result, borrow)
+    }
+
+    // `(self + other) mod m`, for `self, other < m`. We always compute both `s = self + other`
+    // and `s - m`, and then select. `s - m` is right if the addition overflowed, or if it does not
+    // borrow.
+    pub fn add_mod(&self, other: &Self, m: &Self) -> Self {
+        let (sum, carry) = self.overflowing_add(other);
+        let (reduced, borrow) = sum.overflowing_sub(m);
+        let use_reduced = Choice::from_bit(carry).or(Choice::from_bit(borrow).not());
+        Self::select(&sum, &reduced, use_reduced)
+    }
+
+    // `(self - other) mod m`, for `self, other < m`: if the subtraction borrows, add `m` back.
+    pub fn sub_mod(&self, other: &Self, m: &Self) -> Self {
+        let (diff, borrow) = self.overflowing_sub(other);
+        let (corrected, _) = diff.overflowing_add(m);
+        Self::select(&diff, &corrected, Choice::from_bit(borrow))
+    }
+
+    // `(self * other) mod m`, for `self, other < m`. Division is hard to do in constant time, so
+    // we avoid it: going through the bits of `other` from the top, we double the accumulator and
+    // conditionally add `self`, reducing after each step. That is `2 * 64N` modular additions,
+    // much slower than `BigInt`, but every call does the very same work.
+    pub fn mul_mod(&self, other: &Self, m: &Self) -> Self {
+        let mut acc = Self::zero();
+        for i in (0..64 * N).rev() {
+            acc = acc.add_mod(&acc, m);
+            let with_self = acc.add_mod(self, m);
+            let bit = (other.data[i / 64] >> (i % 64)) & 1;
+            acc = Self::select(&acc, &with_self, Choice::from_bit(bit));
+        }
+        acc
+    }
+
+    // `self^exp mod m`, for `self < m` and `m > 1`. We always multiply, and select whether to keep
+    // the product, so the exponent's bits do not show in the sequence of operations.
+    pub fn pow_mod(&self, exp: &Self, m: &Self) -> Self {
+        let mut result = Self::one();
+        for i in (0..64 * N).rev() {
+            result = result.mul_mod(&result, m);
+            let with_base = result.mul_mod(self, m);
+            let bit = (exp.data[i / 64] >> (i % 64)) & 1;
+            result = Self::select(&result, &with_base, Choice::from_bit(bit));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type U128 = CtUint<2>;
+
+    fn ct(x: u128) -> U128 {
+        U128::from_bigint(&BigInt::from(x)).unwrap()
+    }
+
+    #[test]
+    fn test_select_compare() {
+        let (mut a, mut b) = (ct(5), ct(1 << 100));
+        let big = |x: U128| x.to_bigint();
+        assert_eq!(big(U128::select(&a, &b, Choice::from_bit(1))), big(b));
+        assert_eq!(big(U128::select(&a, &b, Choice::from_bit(0))), big(a));
+        U128::swap(&mut a, &mut b, Choice::from_bit(1));
+        assert_eq!((big(a), big(b)), (big(ct(1 << 100)), big(ct(5))));
+        assert!(b.ct_lt(&a).declassify() && !a.ct_lt(&b).declassify());
+        assert!(a.ct_eq(&ct(1 << 100)).declassify() && !a.ct_eq(&b).declassify());
+        assert!(U128::zero().ct_is_zero().declassify());
+        assert!(Choice::from_bit(1)
+            .and(Choice::from_bit(0))
+            .not()
+            .declassify());
+    }
+
+    #[test]
+    fn test_modular() {
+        // A modulus using the full width, so that sums overflow.
+        let m = u128::MAX - 158; // the largest prime below 2^128
+        let (x, y) = (m - 1, m - 2);
+        let (a, b, mm) = (ct(x), ct(y), ct(m));
+        assert!(a.add_mod(&b, &mm).ct_eq(&ct(m - 3)).declassify());
+        assert!(b.sub_mod(&a, &mm).ct_eq(&ct(m - 1)).declassify());
+        // `(-1) * (-2) = 2`.
+        assert!(a.mul_mod(&b, &mm).ct_eq(&ct(2)).declassify());
+        assert!(U128::from_bigint(&BigInt::from_vec(vec![1, 2, 3])).is_none());
+
+        // Compare against the variable-time `BigInt` arithmetic.
+        let (e, big_m) = (BigInt::from(1u128 << 90 | 12345), BigInt::from(m));
+        let expected = BigInt::new(3).mod_pow(&e, &big_m);
+        let result = ct(3).pow_mod(&U128::from_bigint(&e).unwrap(), &mm);
+        assert_eq!(result.to_bigint(), expected);
+    }
+}
@@ -0,0 +1,446 @@
+// Elliptic Curves over Prime Fields
+// =================================
+
+// Elliptic curve cryptography needs only modest numbers, 256 bits for the common curves, but lots
+// of modular arithmetic on them. `ModInt` already is a prime field element when its modulus is
+// prime, so this module only adds the geometry. Like the RSA module, this is for learning and
+// testing: the arithmetic underneath is not constant-time.
+use modint::ModInt;
+use part05::BigInt;
+use std::{fmt, mem, ops};
+
+// A point on a curve. We store it in *Jacobian* coordinates: `(X, Y, Z)` stands for the affine
+// point `(X/Z^2, Y/Z^3)`. That way, adding points needs no field inversion, which is by far the
+// most expensive operation; we only invert once at the end, in `to_affine`. `Z = 0` is the point
+// at infinity, the neutral element of the group.
+#[derive(Clone)]
+pub struct Point {
+    x: ModInt,
+    y: ModInt,
+    z: ModInt,
+}
+
+impl Point {
+    pub fn is_infinity(&self) -> bool {
+        self.z.value().is_zero()
+    }
+
+    // The affine coordinates, or `None` for the point at infinity.
+    pub fn to_affine(&self) -> Option<(BigInt, BigInt)> {
+        let z_inv = self.z.inverse()?;
+        let z_inv2 = &z_inv * &z_inv;
+        let x = &self.x * &z_inv2;
+        let y = &self.y * &(&z_inv2 * &z_inv);
+        Some((x.value().clone(), y.value().clone()))
+    }
+}
+
+// The same point has many Jacobian representations, so we compare `X1 * Z2^2 = X2 * Z1^2` and
+// `Y1 * Z2^3 = Y2 * Z1^3` instead of the coordinates themselves.
+impl PartialEq for Point {
+    fn eq(&self, other: &Point) -> bool {
+        if self.is_infinity() || other.is_infinity() {
+            return self.is_infinity() && other.is_infinity();
+        }
+        let (z1z1, z2z2) = (&self.z * &self.z, &other.z * &other.z);
+        &self.x * &z2z2 == &other.x * &z1z1
+            && &(&self.y * &z2z2) * &other.z == &(&other.y * &z1z1) * &self.z
+    }
+}
+
+impl Eq for Point {}
+
+impl ops::Neg for &Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Point {
+            x: self.x.clone(),
+            y: -&self.y,
+            z: self.z.clone(),
+        }
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.to_affine() {
+            None => write!(f, "infinity"),
+            Some((x, y)) => write!(f, "({:#x}, {:#x})", x, y),
+        }
+    }
+}
+
+impl fmt::Debug for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// ## Curves
+
+// A curve `y^2 = x^3 + ax + b` over the field with `p` elements (the *short Weierstrass* form),
+// together with a base point `G` whose order `n` is prime. We assume all points we compute with
+// lie in the group generated by `G`; for the built-in curves, that is every point on the curve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Curve {
+    p: BigInt,
+    a: ModInt,
+    b: ModInt,
+    g: Point,
+    n: BigInt,
+}
+
+// Parses one of the hex constants below.
+fn hex(s: &str) -> BigInt {
+    BigInt::parse_hex(s).unwrap()
+}
+
+impl Curve {
+    // Returns `None` if the curve is singular, or `G` is not on it. We do not check that `p` and
+    // `n` are prime, or that `n` is the order of `G`.
+    pub fn new(p: BigInt, a: BigInt, b: BigInt, g: (BigInt, BigInt), n: BigInt) -> Option<Curve> {
+        let a = ModInt::new(&a, &p);
+        let b = ModInt::new(&b, &p);
+        // The curve is singular if the discriminant `4a^3 + 27b^2` vanishes.
+        let small = |k: u64| ModInt::new(&BigInt::new(k), &p);
+        let disc = &(small(4) * &a * &a * &a) + &(small(27) * &b * &b);
+        if disc.value().is_zero() {
+            return None;
+        }
+        let one = small(1);
+        let mut curve = Curve {
+            g: Point {
+                x: one.clone(),
+                y: one.clone(),
+                z: small(0),
+            },
+            p,
+            a,
+            b,
+            n,
+        };
+        curve.g = curve.point(&g.0, &g.1)?;
+        Some(curve)
+    }
+
+    // The curve used by Bitcoin, from SEC 2. It has `a = 0`, which makes doubling cheaper.
+    pub fn secp256k1() -> Curve {
+        Curve::new(
+            hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F"),
+            BigInt::zero(),
+            BigInt::new(7),
+            (
+                hex("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"),
+                hex("483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8"),
+            ),
+            hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141"),
+        )
+        .unwrap()
+    }
+
+    // NIST P-256, also known as secp256r1, from FIPS 186-4. It has `a = -3`.
+    pub fn p256() -> Curve {
+        let p = hex("FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF");
+        Curve::new(
+            p.clone(),
+            (&p - &BigInt::new(3)).unwrap(),
+            hex("5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B"),
+            (
+                hex("6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296"),
+                hex("4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5"),
+            ),
+            hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"),
+        )
+        .unwrap()
+    }
+
+    // The field modulus `p`.
+    pub fn modulus(&self) -> &BigInt {
+        &self.p
+    }
+
+    // The order `n` of the base point.
+    pub fn order(&self) -> &BigInt {
+        &self.n
+    }
+
+    pub fn generator(&self) -> &Point {
+        &self.g
+    }
+
+    pub fn infinity(&self) -> Point {
+        Point {
+            x: self.small(1),
+            y: self.small(1),
+            z: self.small(0),
+        }
+    }
+
+    // The point with the given affine coordinates, if it is on the curve.
+    pub fn point(&self, x: &BigInt, y: &BigInt) -> Option<Point> {
+        if *x >= self.p || *y >= self.p {
+            return None;
+        }
+        let pt = Point {
+            x: ModInt::new(x, &self.p),
+            y: ModInt::new(y, &self.p),
+            z: self.small(1),
+        };
+        if self.contains(&pt) {
+            Some(pt)
+        } else {
+            None
+        }
+    }
+
+    // Compressed points (SEC 1) store only `x` and whether `y` is odd. We recover `y` as a square
+    // root of `x^3 + ax + b`; the other root is `p - y`, which has the opposite parity.
+    pub fn decompress(&self, x: &BigInt, y_odd: bool) -> Option<Point> {
+        let fx = ModInt::new(x, &self.p);
+        let rhs = &fx * &fx * &fx + &self.a * &fx + self.b.clone();
+        let y = rhs.value().sqrt_mod(&self.p)?;
+        let y = if y.is_odd() == y_odd {
+            y
+        } else {
+            (&self.p - &y).unwrap()
+        };
+        // For `y = 0`, `p - y` is not a valid coordinate, and `point` rejects it.
+        self.point(x, &y)
+    }
+
+    // Checks the curve equation in Jacobian form, `Y^2 = X^3 + a X Z^4 + b Z^6`.
+    pub fn contains(&self, pt: &Point) -> bool {
+        if pt.is_infinity() {
+            return true;
+        }
+        let z2 = &pt.z * &pt.z;
+        let z4 = &z2 * &z2;
+        let rhs = &pt.x * &pt.x * &pt.x + &self.a * &pt.x * &z4 + &self.b * &z4 * &z2;
+        &pt.y * &pt.y == rhs
+    }
+
+    fn small(&self, k: u64) -> ModInt {
+        ModInt::new(&BigInt::new(k), &self.p)
+    }
+
+    // ## Group law
+
+    // Doubling, following the formulas for Jacobian coordinates from the Explicit-Formulas
+    // Database (`dbl-1998-cmo-2`, for general `a`).
+    pub fn double(&self, pt: &Point) -> Point {
+        // A point with `y = 0` is its own negation, so doubling it gives infinity.
+        if pt.is_infinity() || pt.y.value().is_zero() {
+            return self.infinity();
+        }
+        let xx = &pt.x * &pt.x;
+        let yy = &pt.y * &pt.y;
+        let zz = &pt.z * &pt.z;
+        let s = self.small(4) * &pt.x * &yy;
+        let m = self.small(3) * &xx + &self.a * &(&zz * &zz);
+        let x = &m * &m - self.small(2) * &s;
+        let y = &m * &(&s - &x) - self.small(8) * &(&yy * &yy);
+        let z = self.small(2) * &pt.y * &pt.z;
+        Point { x, y, z }
+    }
+
+    // Addition (`add-1998-cmo-2`). The formulas do not work for equal inputs, so those go to
+    // `double`.
+    pub fn add(&self, p1: &Point, p2: &Point) -> Point {
+        if p1.is_infinity() {
+            return p2.clone();
+        }
+        if p2.is_infinity() {
+            return p1.clone();
+        }
+        let z1z1 = &p1.z * &p1.z;
+        let z2z2 = &p2.z * &p2.z;
+        let u1 = &p1.x * &z2z2;
+        let u2 = &p2.x * &z1z1;
+        let s1 = &p1.y * &p2.z * &z2z2;
+        let s2 = &p2.y * &p1.z * &z1z1;
+        if u1 == u2 {
+            // Same `x`: either the same point, or one is the negation of the other.
+            return if s1 == s2 {
+                self.double(p1)
+            } else {
+                self.infinity()
+            };
+        }
+        let h = &u2 - &u1;
+        let r = &s2 - &s1;
+        let hh = &h * &h;
+        let hhh = &h * &hh;
+        let v = &u1 * &hh;
+        let x = &r * &r - &hhh - self.small(2) * &v;
+        let y = &r * &(&v - &x) - &s1 * &hhh;
+        let z = &h * &p1.z * &p2.z;
+        Point { x, y, z }
+    }
+
+    // Computes `k * pt` with the Montgomery ladder. We keep `r1 = r0 + pt` throughout, and for
+    // every bit of the scalar do exactly one addition and one doubling; the bit only decides which
+    // of the two points gets doubled. We always go through as many bits as `n` has, so the
+    // sequence of operations does not depend on the scalar. (The field arithmetic below still
+    // does, so this does not make the code constant-time.)
+    pub fn mul(&self, k: &BigInt, pt: &Point) -> Point {
+        let k = k % &self.n;
+        let mut r0 = self.infinity();
+        let mut r1 = pt.clone();
+        for i in (0..self.n.bits()).rev() {
+            let bit = k.bit(i);
+            if bit {
+                mem::swap(&mut r0, &mut r1);
+            }
+            r1 = self.add(&r0, &r1);
+            r0 = self.double(&r0);
+            if bit {
+                mem::swap(&mut r0, &mut r1);
+            }
+        }
+        r0
+    }
+
+    // ## Protocols
+
+    // The public key belonging to the secret scalar `d` is `d * G`.
+    pub fn public_key(&self, secret: &BigInt) -> Point {
+        self.mul(secret, &self.g)
+    }
+
+    // Elliptic-curve Diffie-Hellman: both sides compute `a * b * G`, and use its `x` coordinate as
+    // shared secret. Returns `None` if the peer's key is not a valid point, or the result is
+    // infinity.
+    pub fn ecdh(&self, secret: &BigInt, peer: &Point) -> Option<BigInt> {
+        if !self.contains(peer) {
+            return None;
+        }
+        Some(self.mul(secret, peer).to_affine()?.0)
+    }
+
+    // ECDSA works on the leftmost `n.bits()` bits of the message hash.
+    fn hash_to_scalar(&self, hash: &[u8]) -> BigInt {
+        let e = BigInt::from_bytes_be(hash);
+        let excess = (8 * hash.len()).saturating_sub(self.n.bits());
+        &e >> excess
+    }
+
+    // Signs a message hash with the secret `d` and the nonce `k`, giving `(r, s)`. The nonce must
+    // be secret and never reused, or `d` can be recovered; RFC 6979 describes how to derive it
+    // deterministically. Returns `None` for the rare `k` that lead to `r = 0` or `s = 0`.
+    pub fn ecdsa_sign(&self, secret: &BigInt, hash: &[u8], k: &BigInt) -> Option<(BigInt, BigInt)> {
+        let n = &self.n;
+        let r = &self.mul(k, &self.g).to_affine()?.0 % n;
+        let z = self.hash_to_scalar(hash);
+        let s = ModInt::new(&k.mod_inverse(n)?, n) * ModInt::new(&(&z + &(&r * secret)), n);
+        if r.is_zero() || s.value().is_zero() {
+            return None;
+        }
+        Some((r, s.value().clone()))
+    }
+
+    // Checks that `(r, s)` is a signature of the hash for the public key: with `w = s^-1 mod n`,
+    // the point `(z w) G + (r w) Q` must have `x = r (mod n)`.
+    pub fn ecdsa_verify(&self, public: &Point, hash: &[u8], signature: &(BigInt, BigInt)) -> bool {
+        let (r, s) = signature;
+        let n = &self.n;
+        if r.is_zero() || s.is_zero() || r >= n || s >= n {
+            return false;
+        }
+        if public.is_infinity() || !self.contains(public) {
+            return false;
+        }
+        let w = s.mod_inverse(n).unwrap();
+        let u1 = &(&self.hash_to_scalar(hash) * &w) % n;
+        let u2 = &(r * &w) % n;
+        let x = self.add(&self.mul(&u1, &self.g), &self.mul(&u2, public));
+        match x.to_affine() {
+            Some((x, _)) => &x % n == *r,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(curve: &Curve, x: &str, y: &str) -> Point {
+        curve.point(&hex(x), &hex(y)).unwrap()
+    }
+
+    #[test]
+    fn test_known_points() {
+        let k1 = Curve::secp256k1();
+        let g = k1.generator();
+        let g2 = point(
+            &k1,
+            "C6047F9441ED7D6D3045406E95C07CD85C778E4B8CEF3CA7ABAC09B95C709EE5",
+            "1AE168FEA63DC339A3C58419466CEAEEF7F632653266D0E1236431A950CFE52A",
+        );
+        let g3 = point(
+            &k1,
+            "F9308A019258C31049344F85F89D5229B531C845836F99B08601F113BCE036F9",
+            "388F7B0F632DE8140FE337E62A37F3566500A99934C2231B6CB9FD7584B8E672",
+        );
+        assert_eq!(k1.double(g), g2);
+        assert_eq!(k1.add(g, &g2), g3);
+        assert_eq!(k1.mul(&BigInt::new(3), g), g3);
+        assert!(k1.mul(k1.order(), g).is_infinity());
+        let n_1 = (k1.order() - &BigInt::one()).unwrap();
+        assert_eq!(k1.mul(&n_1, g), -g);
+        assert_eq!(k1.add(g, &-g), k1.infinity());
+
+        let p256 = Curve::p256();
+        let g3 = point(
+            &p256,
+            "5ECBE4D1A6330A44C8F7EF951D4BF165E6C6B721EFADA985FB41661BC6E7FD6C",
+            "8734640C4998FF7E374B06CE1A64A2ECD82AB036384FB83D9A79B127A27D5032",
+        );
+        assert_eq!(p256.mul(&BigInt::new(3), p256.generator()), g3);
+        assert!(p256.point(&BigInt::one(), &BigInt::one()).is_none());
+        let (x, y) = g3.to_affine().unwrap();
+        assert_eq!(p256.decompress(&x, y.is_odd()), Some(g3.clone()));
+        assert_ne!(p256.decompress(&x, y.is_even()), Some(g3));
+    }
+
+    #[test]
+    fn test_ecdh() {
+        let curve = Curve::secp256k1();
+        let a = hex("1E99423A4ED27608A15A2616A2B0E9E52CED330AC530EDCC32C8FFC6A526AEDD");
+        let b = BigInt::new(0xdead_beef);
+        let (pa, pb) = (curve.public_key(&a), curve.public_key(&b));
+        let shared = curve.ecdh(&a, &pb).unwrap();
+        assert_eq!(curve.ecdh(&b, &pa), Some(shared));
+    }
+
+    // The P-256 / SHA-256 test vector for the message "sample" from RFC 6979, appendix A.2.5.
+    #[test]
+    fn test_ecdsa_rfc6979() {
+        let curve = Curve::p256();
+        let d = hex("C9AFA9D845BA75166B5C215767B1D6934E50C3DB36E89B127B8A622B120F6721");
+        let public = curve.public_key(&d);
+        assert_eq!(
+            public,
+            point(
+                &curve,
+                "60FED4BA255A9D31C961EB74C6356D68C049B8923B61FA6CE669622E60F29FB6",
+                "7903FE1008B8BC99A41AE9E95628BC64F2F1B20C2D7E9F5177A3C294D4462299",
+            )
+        );
+        let hash =
+            hex("AF2BDBE1AA9B6EC1E2ADE1D694F41FC71A831D0268E9891562113D8A62ADD1BF").to_bytes_be();
+        let k = hex("A6E3C57DD01ABE90086538398355DD4C3B17AA873382B0F24D6129493D8AAD60");
+        let signature = curve.ecdsa_sign(&d, &hash, &k).unwrap();
+        assert_eq!(
+            signature,
+            (
+                hex("EFD48B2AACB6A8FD1140DD9CD45E81D69D2C877B56AAF991C34D0EA84EAF3716"),
+                hex("F7CB1C942D657C41D436C7A1B6E29F65F3E900DBB9AFF4064DC4AB2F843ACDA8"),
+            )
+        );
+        assert!(curve.ecdsa_verify(&public, &hash, &signature));
+        let mut tampered = hash.clone();
+        tampered[0] ^= 1;
+        assert!(!curve.ecdsa_verify(&public, &tampered, &signature));
+    }
+}
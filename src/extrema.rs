@@ -0,0 +1,232 @@
+// Minimum, Maximum and Where They Are
+// ===================================
+
+// The `vec_min`s of the tutorial take a `Vec<T>` (parts 00 to 02) or a `&Vec<T>` (parts 04 and
+// 07), and only ever return the value. Here they work on anything we can iterate over: a `Vec`, a
+// slice, a borrowed `&Vec<BigInt>` (then the results are borrowed as well, as in part 07, and no
+// `BigInt` is copied), or a chain of iterator adapters. Like the minimum of part 07, they only need
+// `PartialOrd`. Values that do not even compare with themselves, like NaN, are skipped, so they
+// never become the minimum or maximum. The `_by` versions leave that to the comparator.
+//
+// Ties are broken like in the standard library: the minimum is the first of several equal
+// elements, and the maximum is the last one.
+use std::cmp::Ordering;
+
+fn partial_cmp<T: PartialOrd>(a: &T, b: &T) -> Ordering {
+    a.partial_cmp(b).unwrap_or(Ordering::Equal)
+}
+
+fn comparable<T: PartialOrd>(x: &T) -> bool {
+    x.partial_cmp(x).is_some()
+}
+
+// ## Comparators and keys
+
+// Everything below is built on these two. `cmp` says how two elements compare.
+pub fn min_by<I, F>(iter: I, mut cmp: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    iter.into_iter().fold(None, |min, e| match min {
+        Some(m) if cmp(&e, &m) != Ordering::Less => Some(m),
+        _ => Some(e),
+    })
+}
+
+pub fn max_by<I, F>(iter: I, mut cmp: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    iter.into_iter().fold(None, |max, e| match max {
+        Some(m) if cmp(&e, &m) == Ordering::Less => Some(m),
+        _ => Some(e),
+    })
+}
+
+// Compares the elements by `key`, which is computed once per element, like in the standard
+// library. We keep it next to the element, and skip elements whose key is NaN.
+pub fn min_by_key<I, K, F>(iter: I, mut key: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    K: PartialOrd,
+    F: FnMut(&I::Item) -> K,
+{
+    let keyed = iter.into_iter().map(|e| (key(&e), e));
+    min_by(keyed.filter(|p| comparable(&p.0)), |a, b| {
+        partial_cmp(&a.0, &b.0)
+    })
+    .map(|p| p.1)
+}
+
+pub fn max_by_key<I, K, F>(iter: I, mut key: F) -> Option<I::Item>
+where
+    I: IntoIterator,
+    K: PartialOrd,
+    F: FnMut(&I::Item) -> K,
+{
+    let keyed = iter.into_iter().map(|e| (key(&e), e));
+    max_by(keyed.filter(|p| comparable(&p.0)), |a, b| {
+        partial_cmp(&a.0, &b.0)
+    })
+    .map(|p| p.1)
+}
+
+pub fn min<I: IntoIterator>(iter: I) -> Option<I::Item>
+where
+    I::Item: PartialOrd,
+{
+    min_by(iter.into_iter().filter(comparable), partial_cmp)
+}
+
+pub fn max<I: IntoIterator>(iter: I) -> Option<I::Item>
+where
+    I::Item: PartialOrd,
+{
+    max_by(iter.into_iter().filter(comparable), partial_cmp)
+}
+
+// ## Positions
+
+// The index of the minimum, counting from zero. We number the elements and compare only the values.
+pub fn argmin<I: IntoIterator>(iter: I) -> Option<usize>
+where
+    I::Item: PartialOrd,
+{
+    let numbered = iter.into_iter().enumerate().filter(|p| comparable(&p.1));
+    min_by(numbered, |a, b| partial_cmp(&a.1, &b.1)).map(|(i, _)| i)
+}
+
+pub fn argmax<I: IntoIterator>(iter: I) -> Option<usize>
+where
+    I::Item: PartialOrd,
+{
+    let numbered = iter.into_iter().enumerate().filter(|p| comparable(&p.1));
+    max_by(numbered, |a, b| partial_cmp(&a.1, &b.1)).map(|(i, _)| i)
+}
+
+// ## Both at once
+
+// Finding the minimum and then the maximum takes `2n` comparisons. Taking the elements in pairs,
+// we compare the two with each other first, and then only the smaller one with the minimum, and
+// the larger one with the maximum. That is three comparisons for two elements, or about `1.5n`.
+// A single element is both minimum and maximum, which is why we need `Clone`. For borrowed
+// elements, that only copies the reference.
+pub fn minmax_by<I, F>(iter: I, mut cmp: F) -> Option<(I::Item, I::Item)>
+where
+    I: IntoIterator,
+    I::Item: Clone,
+    F: FnMut(&I::Item, &I::Item) -> Ordering,
+{
+    let mut iter = iter.into_iter();
+    let first = iter.next()?;
+    let (mut min, mut max) = (first.clone(), first);
+    loop {
+        let a = match iter.next() {
+            None => return Some((min, max)),
+            Some(a) => a,
+        };
+        // With an odd number of elements, the last one is compared with both.
+        let (small, large) = match iter.next() {
+            None => (a.clone(), a),
+            Some(b) => {
+                if cmp(&b, &a) == Ordering::Less {
+                    (b, a)
+                } else {
+                    (a, b)
+                }
+            }
+        };
+        if cmp(&small, &min) == Ordering::Less {
+            min = small;
+        }
+        if cmp(&large, &max) != Ordering::Less {
+            max = large;
+        }
+    }
+}
+
+pub fn minmax<I: IntoIterator>(iter: I) -> Option<(I::Item, I::Item)>
+where
+    I::Item: PartialOrd + Clone,
+{
+    minmax_by(iter.into_iter().filter(comparable), partial_cmp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use part05::BigInt;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_extrema() {
+        let v: Vec<i32> = vec![18, 5, 7, 3, 9, 27, 3];
+        assert_eq!(min(&v), Some(&3));
+        assert_eq!(max(v.iter().map(|x| x % 10)), Some(9));
+        assert_eq!(argmin(&v), Some(3));
+        assert_eq!(argmax(&v), Some(5));
+        assert_eq!(argmin(Vec::<i32>::new()), None);
+        assert_eq!(min_by_key(&v, |x| (*x - 8).abs()), Some(&7));
+        assert_eq!(
+            max_by(vec!["ab", "c", "de"], |a, b| a.len().cmp(&b.len())),
+            Some("de")
+        );
+        assert_eq!(minmax(vec![4.0, -1.5, 2.0]), Some((-1.5, 4.0)));
+    }
+
+    #[test]
+    fn test_nan() {
+        let nan = f64::NAN;
+        let v = [4.0, nan, -1.5, 2.0];
+        assert_eq!(min(v), Some(-1.5));
+        assert_eq!(max([3.0, nan, 1.0]), Some(3.0));
+        assert_eq!(argmin(v), Some(2));
+        assert_eq!(argmax([3.0, nan, 1.0]), Some(0));
+        // `minmax` compares elements in pairs; a NaN must not hide its partner.
+        assert_eq!(minmax([3.0, 1.0, nan, 5.0, 0.5]), Some((0.5, 5.0)));
+        assert_eq!(minmax([2.0, nan, 0.0]), Some((0.0, 2.0)));
+        assert_eq!(max_by_key(v, |x| x * 2.0), Some(4.0));
+        assert_eq!(min([nan, nan]), None);
+    }
+
+    #[test]
+    fn test_borrowed_bigints() {
+        let v = vec![
+            BigInt::new(42),
+            BigInt::from_vec(vec![0, 1]),
+            BigInt::new(1),
+        ];
+        // The results point into `v`.
+        assert!(std::ptr::eq(min(&v).unwrap(), &v[2]));
+        assert_eq!(argmax(&v), Some(1));
+        let (lo, hi) = minmax(&v).unwrap();
+        assert!(std::ptr::eq(lo, &v[2]) && std::ptr::eq(hi, &v[1]));
+        assert_eq!(max_by_key(&v, |b| b.bits() % 64), Some(&v[0]));
+    }
+
+    #[test]
+    fn test_minmax_comparisons() {
+        let count = Cell::new(0);
+        let v: Vec<u32> = (0..1000).map(|i| (i * 7919) % 1000).collect();
+        let result = minmax_by(&v, |a, b| {
+            count.set(count.get() + 1);
+            a.cmp(b)
+        });
+        assert_eq!(result, Some((&0, &999)));
+        assert!(count.get() <= 1500);
+        // Ties: the first minimum and the last maximum, as in the standard library.
+        let pairs = [(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd')];
+        let (lo, hi) = minmax_by(&pairs, |a, b| a.0.cmp(&b.0)).unwrap();
+        assert_eq!((lo.1, hi.1), ('b', 'c'));
+        assert_eq!(
+            pairs.iter().min_by_key(|p| p.0),
+            min_by_key(&pairs, |p| p.0)
+        );
+        assert_eq!(
+            pairs.iter().max_by_key(|p| p.0),
+            max_by_key(&pairs, |p| p.0)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+// Floating Point Minimum and NaN
+// ==============================
+
+// `f32` and `f64` are only partially ordered: NaN ("not a number", e.g., the result of `0.0 / 0.0`
+// or of parsing `"NaN"`) is neither smaller, nor larger, nor equal to anything, itself included.
+// The `Minimum for f32` of part 02 used to be `if self < b { self } else { b }`, so a NaN in the
+// input could come out as the minimum or not, depending on where it was. Here we make the choice
+// explicit, with one of three policies:
+//
+// - `Propagate`: a NaN makes the whole result NaN, like any other arithmetic with NaN. This is
+//   `minimum` from IEEE 754-2019, which also takes `-0.0` to be smaller than `0.0`.
+// - `Ignore`: NaNs are skipped, as if they were not there. This is `minNum` from IEEE 754-2008, and
+//   what `f64::min` does.
+// - `TotalOrder`: the total order of IEEE 754, where `-NaN < -inf < ... < -0.0 < 0.0 < ... < inf <
+//   NaN`. This is what `f64::total_cmp` implements.
+use part02::Minimum;
+use std::cmp::Ordering;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NanPolicy {
+    Propagate,
+    Ignore,
+    TotalOrder,
+}
+
+// What we need from a float type. Both methods exist for `f32` and `f64`, but not as a trait.
+pub trait Float: Copy + PartialOrd + fmt::Display {
+    fn is_nan(self) -> bool;
+    fn is_sign_negative(self) -> bool;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {$(
+        impl Float for $t {
+            fn is_nan(self) -> bool {
+                <$t>::is_nan(self)
+            }
+            fn is_sign_negative(self) -> bool {
+                <$t>::is_sign_negative(self)
+            }
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                <$t>::total_cmp(self, other)
+            }
+        }
+    )*};
+}
+
+impl_float!(f32, f64);
+
+// ## Comparing two numbers
+
+// The smaller of `a` and `b`. As in `vec_min`, `a` wins ties.
+pub fn min_with<T: Float>(a: T, b: T, policy: NanPolicy) -> T {
+    match policy {
+        NanPolicy::Propagate if a.is_nan() => a,
+        NanPolicy::Propagate if b.is_nan() => b,
+        // The only equal numbers that differ are `0.0` and `-0.0`.
+        NanPolicy::Propagate if a == b && b.is_sign_negative() => b,
+        NanPolicy::Ignore if a.is_nan() => b,
+        NanPolicy::Ignore if b.is_nan() => a,
+        NanPolicy::TotalOrder if b.total_cmp(&a) == Ordering::Less => b,
+        NanPolicy::TotalOrder => a,
+        _ if b < a => b,
+        _ => a,
+    }
+}
+
+// A total order for sorting. Apart from `TotalOrder`, we put all NaNs at the end, where they are easy
+// to find and cut off.
+pub fn cmp_with<T: Float>(a: &T, b: &T, policy: NanPolicy) -> Ordering {
+    match policy {
+        NanPolicy::TotalOrder => a.total_cmp(b),
+        _ => match (a.is_nan(), b.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => a.partial_cmp(b).unwrap(),
+        },
+    }
+}
+
+// ## `Minimum` with a policy
+
+// The `Minimum` trait of part 02 has no room for a policy, so we choose it with the type: wrap the
+// numbers in one of these before calling `part02::vec_min`. The plain `f32` and `f64` impls in
+// part 02 propagate NaN.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropagateNan<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IgnoreNan<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TotalOrder<T>(pub T);
+
+impl<T: Float> Minimum for PropagateNan<T> {
+    fn min(self, b: Self) -> Self {
+        PropagateNan(min_with(self.0, b.0, NanPolicy::Propagate))
+    }
+}
+
+impl<T: Float> Minimum for IgnoreNan<T> {
+    fn min(self, b: Self) -> Self {
+        IgnoreNan(min_with(self.0, b.0, NanPolicy::Ignore))
+    }
+}
+
+impl<T: Float> Minimum for TotalOrder<T> {
+    fn min(self, b: Self) -> Self {
+        TotalOrder(min_with(self.0, b.0, NanPolicy::TotalOrder))
+    }
+}
+
+// ## Reporting NaNs
+
+// The minimum, and how many NaNs the input had. With `Ignore`, `min` is only NaN if all inputs
+// were.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatMin<T> {
+    pub min: Option<T>,
+    pub nans: usize,
+}
+
+pub fn vec_min<T: Float>(v: &[T], policy: NanPolicy) -> FloatMin<T> {
+    let mut result = FloatMin { min: None, nans: 0 };
+    for &e in v {
+        if e.is_nan() {
+            result.nans += 1;
+        }
+        result.min = Some(match result.min {
+            None => e,
+            Some(m) => min_with(m, e, policy),
+        });
+    }
+    result
+}
+
+impl<T: Float> fmt::Display for FloatMin<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.min {
+            None => write!(f, "<nothing>")?,
+            Some(m) => write!(f, "{}", m)?,
+        }
+        if self.nans > 0 {
+            write!(f, " ({} NaN inputs)", self.nans)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use part02::{vec_min as part02_vec_min, Something};
+    const NAN: f64 = f64::NAN;
+
+    #[test]
+    fn test_policies() {
+        // The NaN in the middle used to decide the result depending on its position.
+        let v = [3.0, NAN, -1.0, 2.0];
+        let result = |policy| vec_min(&v, policy);
+        assert!(result(NanPolicy::Propagate).min.unwrap().is_nan());
+        assert_eq!(result(NanPolicy::Ignore).min, Some(-1.0));
+        assert_eq!(result(NanPolicy::TotalOrder).min, Some(-1.0));
+        assert_eq!(result(NanPolicy::Ignore).nans, 1);
+        assert_eq!(
+            vec_min(&[-NAN, 1.0], NanPolicy::TotalOrder)
+                .min
+                .unwrap()
+                .to_bits(),
+            (-NAN).to_bits()
+        );
+        for policy in [NanPolicy::Propagate, NanPolicy::TotalOrder] {
+            assert_eq!(
+                min_with(0.0f64, -0.0, policy).to_bits(),
+                (-0.0f64).to_bits()
+            );
+            assert_eq!(
+                min_with(-0.0f64, 0.0, policy).to_bits(),
+                (-0.0f64).to_bits()
+            );
+        }
+        assert_eq!(
+            vec_min::<f32>(&[], NanPolicy::Ignore).to_string(),
+            "<nothing>"
+        );
+        assert_eq!(
+            vec_min(&[NAN, 2.5], NanPolicy::Ignore).to_string(),
+            "2.5 (1 NaN inputs)"
+        );
+    }
+
+    #[test]
+    fn test_minimum_wrappers() {
+        let v = [f32::NAN, 4.0, 1.5];
+        let ignoring = part02_vec_min(v.iter().map(|&x| IgnoreNan(x)).collect());
+        assert_eq!(ignoring, Something(IgnoreNan(1.5)));
+        let propagating = part02_vec_min(v.iter().map(|&x| PropagateNan(x)).collect());
+        assert!(propagating.unwrap_or(PropagateNan(0.0)).0.is_nan());
+        // The plain impl of part 02 now propagates as well, no matter where the NaN is.
+        for w in [vec![1.0f32, f32::NAN], vec![f32::NAN, 1.0]] {
+            assert!(part02_vec_min(w).unwrap_or(0.0).is_nan());
+        }
+        let mut sorted = [2.0, NAN, -0.0, 0.0, -3.0];
+        sorted.sort_by(|a, b| cmp_with(a, b, NanPolicy::Ignore));
+        assert_eq!(&sorted[..4], &[-3.0, -0.0, 0.0, 2.0]);
+        assert!(sorted[4].is_nan());
+    }
+}
@@ -0,0 +1,446 @@
+// Reading Numbers
+// ===============
+
+// `read_vec` in part 03 reads from standard input only, panics if reading fails, and answers a
+// line that is not a number with a bare "Input numbers". That is fine for typing in a few
+// numbers, but not for reading a file, or for a test. Here, the input is any `BufRead` (a file
+// wrapped in an `io::BufReader`, standard input, or a byte slice in a test), and errors come back
+// as values that say where and what went wrong. What to do about bad lines is up to the caller.
+use part05::BigInt;
+use std::io::BufRead;
+use std::str::FromStr;
+use std::{error, fmt, io};
+
+// ## Errors
+
+// A line that did not parse. Lines are numbered from 1, like in an editor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LineError<E> {
+    pub line: usize,
+    pub text: String,
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for LineError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {:?} is not a number: {}",
+            self.line, self.text, self.error
+        )
+    }
+}
+
+#[derive(Debug)]
+pub enum ReadError<E> {
+    // Reading failed, e.g., because the input is not valid UTF-8.
+    Io { line: usize, error: io::Error },
+    // A bad line, with `BadLines::Fail`.
+    Parse(LineError<E>),
+    // The header of a table does not have the column we are looking for.
+    MissingColumn { line: usize, name: String },
+}
+
+impl<E: fmt::Display> fmt::Display for ReadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::Io { line, error } => write!(f, "line {}: {}", line, error),
+            ReadError::Parse(e) => e.fmt(f),
+            ReadError::MissingColumn { line, name } => {
+                write!(f, "line {}: there is no column {:?}", line, name)
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> error::Error for ReadError<E> {}
+
+// ## Reading
+
+// What to do with lines that are not numbers. Blank lines are always skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BadLines {
+    // Ignore them.
+    Skip,
+    // Stop at the first one, and return it as the error.
+    Fail,
+    // Keep going, and return them next to the numbers.
+    Collect,
+}
+
+// The numbers, and with `BadLines::Collect`, the lines that were not.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Numbers<T, E> {
+    pub values: Vec<T>,
+    pub errors: Vec<LineError<E>>,
+}
+
+impl<T, E> Numbers<T, E> {
+    // Adds a parsed number, or deals with the error as `policy` says.
+    fn record(
+        &mut self,
+        line: usize,
+        text: &str,
+        result: Result<T, E>,
+        policy: BadLines,
+    ) -> Result<(), ReadError<E>> {
+        if let Err(error) = result.map(|x| self.values.push(x)) {
+            let e = LineError {
+                line,
+                text: text.to_string(),
+                error,
+            };
+            match policy {
+                BadLines::Skip => {}
+                BadLines::Fail => return Err(ReadError::Parse(e)),
+                BadLines::Collect => self.errors.push(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+pub fn read_vec<T: FromStr, R: BufRead>(
+    input: R,
+    policy: BadLines,
+) -> Result<Numbers<T, T::Err>, ReadError<T::Err>> {
+    let mut numbers = Numbers {
+        values: vec![],
+        errors: vec![],
+    };
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|error| ReadError::Io { line: i + 1, error })?;
+        let text = line.trim();
+        if !text.is_empty() {
+            numbers.record(i + 1, text, text.parse(), policy)?;
+        }
+    }
+    Ok(numbers)
+}
+
+// ## Number syntax
+
+// Numbers copied from programs and spreadsheets come in more shapes than `parse` accepts: `0x1F`,
+// `1_000`, or `1e6` for an integer. We rewrite such a token into plain decimal digits, which every
+// number type's `FromStr` understands, and only then parse it. That way, this works for `i32`, `f64`
+// and `BigInt` alike. If the rewritten token does not parse either, we report that error: the
+// token was a number, and it says what is wrong with it, e.g., that it is too large.
+pub fn parse_number<T: FromStr>(token: &str) -> Result<T, T::Err> {
+    let original = token.parse::<T>();
+    if original.is_ok() {
+        return original;
+    }
+    match normalize(token) {
+        Some(plain) => plain.parse::<T>(),
+        None => original,
+    }
+}
+
+// Exponents beyond this would make us write out absurdly long digit strings.
+const MAX_EXPONENT: u32 = 4096;
+
+fn normalize(token: &str) -> Option<String> {
+    let (sign, rest) = match token.as_bytes().first() {
+        Some(b'-') => ("-", &token[1..]),
+        Some(b'+') => ("", &token[1..]),
+        _ => ("", token),
+    };
+    // A second sign, as in `+-5`, does not make a number.
+    if rest.is_empty() || rest.starts_with(['+', '-']) {
+        return None;
+    }
+    let hex = rest.strip_prefix("0x").or_else(|| rest.strip_prefix("0X"));
+    // Underscores may only separate two digits: they may not start or end a number, come in
+    // pairs, or touch a `.`, an exponent or the `0x`.
+    let is_digit = |b: u8| match hex {
+        Some(_) => b.is_ascii_hexdigit(),
+        None => b.is_ascii_digit(),
+    };
+    let digits = hex.unwrap_or(rest).as_bytes();
+    for (i, &b) in digits.iter().enumerate() {
+        if b == b'_'
+            && (i == 0
+                || i + 1 == digits.len()
+                || !is_digit(digits[i - 1])
+                || !is_digit(digits[i + 1]))
+        {
+            return None;
+        }
+    }
+    let rest = hex.unwrap_or(rest).replace('_', "");
+    let rest = rest.as_str();
+    if hex.is_some() {
+        let b = BigInt::parse_hex(rest).ok()?;
+        return Some(format!("{}{}", sign, b));
+    }
+    // A number with an exponent that turns out to be an integer, like `1e6` or `2.5E3`. For floats,
+    // `parse` already handles these, so this is for integer types.
+    let e = match rest.find(['e', 'E']) {
+        Some(e) => e,
+        None => return Some(format!("{}{}", sign, rest)),
+    };
+    let exponent: u32 = rest[e + 1..]
+        .strip_prefix('+')
+        .unwrap_or(&rest[e + 1..])
+        .parse()
+        .ok()?;
+    let (int, frac) = match rest[..e].find('.') {
+        Some(dot) => (&rest[..dot], &rest[dot + 1..e]),
+        None => (&rest[..e], ""),
+    };
+    // Without digits in front of the exponent, `e5` would come out as zero.
+    let digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+    if !digits(int) || !digits(frac) || int.len() + frac.len() == 0 {
+        return None;
+    }
+    let frac = frac.trim_end_matches('0');
+    if exponent > MAX_EXPONENT || frac.len() > exponent as usize {
+        return None;
+    }
+    let zeros = "0".repeat(exponent as usize - frac.len());
+    Some(format!("{}{}{}{}", sign, int, frac, zeros))
+}
+
+// ## Input formats
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Layout {
+    // One number per line, as in part 03.
+    Lines,
+    // Any number of numbers per line, separated by whitespace and/or commas.
+    Values,
+    // A table, like CSV (`,`) or TSV (`\t`) exported from a spreadsheet. The first line is the
+    // header, and we take the numbers from the column with the given name. Fields may be quoted with
+    // `"`, and a quote inside a quoted field is written as `""`.
+    Column { delimiter: char, name: String },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Format {
+    pub layout: Layout,
+    // Everything from this character to the end of the line is ignored. In a table, where a field
+    // might contain it, only lines that start with it are comments.
+    pub comment: Option<char>,
+}
+
+impl Format {
+    // Whitespace- or comma-separated numbers, with `#` comments.
+    pub fn values() -> Self {
+        Format {
+            layout: Layout::Values,
+            comment: Some('#'),
+        }
+    }
+
+    pub fn csv_column(name: &str) -> Self {
+        Format {
+            layout: Layout::Column {
+                delimiter: ',',
+                name: name.to_string(),
+            },
+            comment: Some('#'),
+        }
+    }
+
+    pub fn tsv_column(name: &str) -> Self {
+        Format {
+            layout: Layout::Column {
+                delimiter: '\t',
+                name: name.to_string(),
+            },
+            comment: Some('#'),
+        }
+    }
+}
+
+// Splits a line in the `Values` layout into its numbers, after cutting off a comment.
+pub fn split_values(line: &str, comment: Option<char>) -> impl Iterator<Item = &str> {
+    let text = match comment {
+        Some(c) => line.split(c).next().unwrap(),
+        None => line,
+    };
+    text.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|t| !t.is_empty())
+}
+
+// Splits a table row at `delimiter`, outside of quotes.
+fn split_fields(line: &str, delimiter: char) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().unwrap();
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if c == delimiter && !quoted => fields.push(String::new()),
+            c => field.push(c),
+        }
+    }
+    fields.iter().map(|f| f.trim().to_string()).collect()
+}
+
+// Like `read_vec`, but in the given format, and accepting the number syntax of `parse_number`. In a
+// table, a row that is too short, or has an empty field, counts as a bad line.
+pub fn read_with<T: FromStr, R: BufRead>(
+    input: R,
+    format: &Format,
+    policy: BadLines,
+) -> Result<Numbers<T, T::Err>, ReadError<T::Err>> {
+    let mut numbers = Numbers {
+        values: vec![],
+        errors: vec![],
+    };
+    // For tables: the index of our column, once we have seen the header.
+    let mut column: Option<usize> = None;
+    let is_table = matches!(format.layout, Layout::Column { .. });
+    for (i, line) in input.lines().enumerate() {
+        let line = line.map_err(|error| ReadError::Io { line: i + 1, error })?;
+        // Spreadsheets like to start their CSV exports with a byte order mark.
+        let line = match i {
+            0 => line.strip_prefix('\u{feff}').unwrap_or(&line),
+            _ => &line,
+        };
+        let text = match format.comment {
+            Some(c) if is_table && line.trim_start().starts_with(c) => "",
+            Some(c) if !is_table => line.split(c).next().unwrap(),
+            _ => line,
+        };
+        let tokens: Vec<String> = match format.layout {
+            Layout::Lines => vec![text.trim().to_string()],
+            Layout::Values => split_values(text, None).map(str::to_string).collect(),
+            Layout::Column {
+                delimiter,
+                ref name,
+            } => {
+                if text.trim().is_empty() {
+                    continue;
+                }
+                let fields = split_fields(text, delimiter);
+                match column {
+                    None => {
+                        column = fields.iter().position(|f| f == name);
+                        if column.is_none() {
+                            return Err(ReadError::MissingColumn {
+                                line: i + 1,
+                                name: name.clone(),
+                            });
+                        }
+                        continue;
+                    }
+                    // A missing field is treated like an empty one, and fails to parse.
+                    Some(c) => vec![fields.get(c).cloned().unwrap_or_default()],
+                }
+            }
+        };
+        for token in tokens.iter().filter(|t| is_table || !t.is_empty()) {
+            numbers.record(i + 1, token, parse_number(token), policy)?;
+        }
+    }
+    Ok(numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::ParseIntError;
+
+    fn read(input: &str, policy: BadLines) -> Result<Numbers<i32, ParseIntError>, String> {
+        read_vec(input.as_bytes(), policy).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_policies() {
+        let input = "18\n  5 \n\nseven\n3\n99999999999\n";
+        assert_eq!(read(input, BadLines::Skip).unwrap().values, vec![18, 5, 3]);
+        assert_eq!(
+            read(input, BadLines::Fail).unwrap_err(),
+            "line 4: \"seven\" is not a number: invalid digit found in string"
+        );
+        let collected = read(input, BadLines::Collect).unwrap();
+        assert_eq!(collected.values, vec![18, 5, 3]);
+        let lines: Vec<usize> = collected.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![4, 6]);
+        assert_eq!(
+            collected.errors[1].to_string(),
+            "line 6: \"99999999999\" is not a number: number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn test_io_error() {
+        let input: &[u8] = b"1.5\n2\n\xff\n";
+        let result = read_vec::<f64, _>(input, BadLines::Skip);
+        match result {
+            Err(ReadError::Io { line, error }) => {
+                assert_eq!(line, 3);
+                assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+            }
+            _ => panic!("expected an I/O error"),
+        }
+    }
+
+    #[test]
+    fn test_number_syntax() {
+        let ints: Vec<i32> = ["0x1F", "-0x10", "1_000", "+1e6", "2.5E3", "-12"]
+            .iter()
+            .map(|t| parse_number(t).unwrap())
+            .collect();
+        assert_eq!(ints, vec![31, -16, 1000, 1_000_000, 2500, -12]);
+        for bad in [
+            "1e-3", "1.25e1", "_1", "1__0", "0x", "1e99999", "e5", "-E3", ".e2", "+-5", "-+5",
+            "--5", "+", "-_5", "1_.5", "1._5", "1_e5", "1e_5", "0x_1F", "0_x1F",
+        ] {
+            assert!(parse_number::<i32>(bad).is_err(), "{}", bad);
+        }
+        // A number that is out of range says so, rather than that it has an invalid digit.
+        for large in ["0x1_0000_0000", "1_000_000_000_000", "3e10"] {
+            assert_eq!(
+                parse_number::<i32>(large).unwrap_err().to_string(),
+                "number too large to fit in target type"
+            );
+        }
+        assert_eq!(parse_number::<f64>("1_000.5"), Ok(1000.5));
+        for bad in ["+-1.5", "1_.5", "-_5"] {
+            assert!(parse_number::<f64>(bad).is_err(), "{}", bad);
+        }
+        assert_eq!(parse_number::<i32>("0xE_E"), Ok(0xee));
+        let big: BigInt = parse_number("0x1_0000_0000_0000_0000").unwrap();
+        assert_eq!(big, BigInt::from_vec(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_formats() {
+        let input = "# measurements\n1, 2 3\n\n4,five # six\n7\n";
+        let values = read_with::<i32, _>(input.as_bytes(), &Format::values(), BadLines::Collect);
+        let values = values.unwrap();
+        assert_eq!(values.values, vec![1, 2, 3, 4, 7]);
+        assert_eq!(
+            values.errors[0].to_string().split(':').next(),
+            Some("line 4")
+        );
+
+        let csv = "# exported\nname,\"size, in mm\"\n\"a, b\",12\nc,0x10\nd\ne,\n";
+        let format = Format::csv_column("size, in mm");
+        let sizes = read_with::<u32, _>(csv.as_bytes(), &format, BadLines::Collect).unwrap();
+        assert_eq!(sizes.values, vec![12, 16]);
+        let lines: Vec<usize> = sizes.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![5, 6]);
+
+        let tsv = "x\ty\n1\t1_5\n2\t2e2\n";
+        let ys = read_with::<i64, _>(tsv.as_bytes(), &Format::tsv_column("y"), BadLines::Fail);
+        assert_eq!(ys.unwrap().values, vec![15, 200]);
+        let bom = "\u{feff}size,x\n3,4\n";
+        let sizes =
+            read_with::<i64, _>(bom.as_bytes(), &Format::csv_column("size"), BadLines::Fail);
+        assert_eq!(sizes.unwrap().values, vec![3]);
+        let missing = read_with::<i64, _>(tsv.as_bytes(), &Format::csv_column("y"), BadLines::Fail);
+        assert_eq!(
+            missing.unwrap_err().to_string(),
+            "line 1: there is no column \"y\""
+        );
+    }
+}
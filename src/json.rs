@@ -0,0 +1,597 @@
+// JSON with Exact Numbers
+// =======================
+
+// JSON does not limit the size or precision of numbers, but many readers turn every number into an
+// `f64`. A 256-bit ID then silently loses all but its top 53 bits. This minimal reader and writer
+// keeps integers of any length as `SignedBigInt`, and all other numbers as the exact text they were
+// written as, so a document survives a round trip unchanged.
+use bigdecimal::BigDecimal;
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::{fmt, str};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonNumber {
+    Integer(SignedBigInt),
+    // A number with a fraction or an exponent, like `0.1` or `6.02e23`, exactly as written.
+    Decimal(String),
+}
+
+// Converting a number like `1e1000000000` to a `BigDecimal` would take gigabytes, so
+// `to_decimal` refuses to produce numbers with more than this many digits after or before the
+// point.
+const MAX_DECIMAL_EXPONENT: i64 = 100_000;
+
+impl JsonNumber {
+    pub fn as_integer(&self) -> Option<&SignedBigInt> {
+        match self {
+            JsonNumber::Integer(i) => Some(i),
+            JsonNumber::Decimal(_) => None,
+        }
+    }
+
+    // The exact value as a decimal. `BigDecimal` has no exponent, so we apply it to the scale.
+    // Returns `None` if the exponent is too large (see `MAX_DECIMAL_EXPONENT`).
+    pub fn to_decimal(&self) -> Option<BigDecimal> {
+        let s = match self {
+            JsonNumber::Integer(i) => return Some(BigDecimal::from(i.clone())),
+            JsonNumber::Decimal(s) => s,
+        };
+        let (mantissa, exp) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i64>().ok()?),
+            None => (&s[..], 0),
+        };
+        let d: BigDecimal = mantissa.parse().ok()?;
+        // With `exp = i64::MIN`, even the subtraction overflows.
+        let scale = (d.scale() as i64).checked_sub(exp)?;
+        if scale.unsigned_abs() > MAX_DECIMAL_EXPONENT as u64 {
+            return None;
+        }
+        if scale >= 0 {
+            Some(BigDecimal::new(d.mantissa().clone(), scale as u32))
+        } else {
+            let factor = SignedBigInt::from(BigInt::new(10).pow(-scale as u32));
+            Some(BigDecimal::new(d.mantissa() * &factor, 0))
+        }
+    }
+}
+
+impl fmt::Display for JsonNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonNumber::Integer(i) => write!(f, "{}", i),
+            JsonNumber::Decimal(s) => f.write_str(s),
+        }
+    }
+}
+
+// Objects keep their keys in the order they were read, so writing a document back does not
+// shuffle it. Duplicate keys are kept, too.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(JsonNumber),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    // Looks up a key in an object. Like most JSON readers, we let the last of duplicate keys win.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match self {
+            Json::Object(fields) => fields.iter().rev().find(|f| f.0 == key).map(|f| &f.1),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<&SignedBigInt> {
+        match self {
+            Json::Number(n) => n.as_integer(),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Json::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+impl From<BigInt> for Json {
+    fn from(b: BigInt) -> Self {
+        Json::Number(JsonNumber::Integer(SignedBigInt::from(b)))
+    }
+}
+
+impl From<SignedBigInt> for Json {
+    fn from(i: SignedBigInt) -> Self {
+        Json::Number(JsonNumber::Integer(i))
+    }
+}
+
+// Decimals without a fractional part become integers, so they read back as the same value.
+impl From<BigDecimal> for Json {
+    fn from(d: BigDecimal) -> Self {
+        if d.scale() == 0 {
+            Json::from(d.mantissa().clone())
+        } else {
+            Json::Number(JsonNumber::Decimal(d.to_string()))
+        }
+    }
+}
+
+impl From<bool> for Json {
+    fn from(b: bool) -> Self {
+        Json::Bool(b)
+    }
+}
+
+impl From<&str> for Json {
+    fn from(s: &str) -> Self {
+        Json::String(s.to_string())
+    }
+}
+
+impl From<String> for Json {
+    fn from(s: String) -> Self {
+        Json::String(s)
+    }
+}
+
+// ## Writing
+
+// Quotes and backslashes have to be escaped, and so do control characters.
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("\"")
+}
+
+// We write compact JSON, without any whitespace.
+impl fmt::Display for Json {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Json::Null => f.write_str("null"),
+            Json::Bool(b) => write!(f, "{}", b),
+            Json::Number(n) => write!(f, "{}", n),
+            Json::String(s) => write_string(f, s),
+            Json::Array(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Json::Object(fields) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+// ## Reading
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonErrorKind {
+    UnexpectedEnd,
+    UnexpectedChar(char),
+    InvalidNumber,
+    InvalidEscape,
+    // Arrays and objects nested deeper than `MAX_DEPTH`.
+    TooDeep,
+    TrailingCharacters,
+}
+
+// Besides what went wrong, the error says where: `offset` counts bytes from the start of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParseJsonError {
+    pub kind: JsonErrorKind,
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.kind {
+            JsonErrorKind::UnexpectedEnd => write!(f, "unexpected end of input")?,
+            JsonErrorKind::UnexpectedChar(c) => write!(f, "unexpected character {:?}", c)?,
+            JsonErrorKind::InvalidNumber => write!(f, "invalid number")?,
+            JsonErrorKind::InvalidEscape => write!(f, "invalid escape sequence")?,
+            JsonErrorKind::TooDeep => write!(f, "nesting too deep")?,
+            JsonErrorKind::TrailingCharacters => write!(f, "trailing characters")?,
+        }
+        write!(f, " at offset {}", self.offset)
+    }
+}
+
+// The parser is recursive, so we limit the nesting to keep malicious input from overflowing the
+// stack.
+const MAX_DEPTH: usize = 128;
+
+// A recursive-descent parser. All the structural characters of JSON are ASCII, so we can look at
+// bytes and only need to care about UTF-8 inside strings.
+struct Parser<'a> {
+    s: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, kind: JsonErrorKind) -> ParseJsonError {
+        ParseJsonError {
+            kind,
+            offset: self.pos,
+        }
+    }
+
+    // The error for whatever we found at the current position.
+    fn unexpected(&self) -> ParseJsonError {
+        match self.s[self.pos..].chars().next() {
+            Some(c) => self.error(JsonErrorKind::UnexpectedChar(c)),
+            None => self.error(JsonErrorKind::UnexpectedEnd),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.s.as_bytes().get(self.pos).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), ParseJsonError> {
+        if self.peek() != Some(b) {
+            return Err(self.unexpected());
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn value(&mut self) -> Result<Json, ParseJsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'"') => Ok(Json::String(self.string()?)),
+            Some(b'[') => self.nested(Parser::array),
+            Some(b'{') => self.nested(Parser::object),
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn literal(&mut self, word: &str, value: Json) -> Result<Json, ParseJsonError> {
+        for &b in word.as_bytes() {
+            self.expect(b)?;
+        }
+        Ok(value)
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<Json, ParseJsonError>,
+    ) -> Result<Json, ParseJsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(JsonErrorKind::TooDeep));
+        }
+        self.depth += 1;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    // Skips a run of digits, returning how many there were.
+    fn digits(&mut self) -> usize {
+        let start = self.pos;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.pos += 1;
+        }
+        self.pos - start
+    }
+
+    // The JSON grammar is stricter than Rust's: no leading zeros, no `+`, and digits on both sides
+    // of the point.
+    fn number(&mut self) -> Result<Json, ParseJsonError> {
+        let start = self.pos;
+        let invalid = |p: &Parser| ParseJsonError {
+            kind: JsonErrorKind::InvalidNumber,
+            offset: p.pos,
+        };
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => {
+                self.digits();
+            }
+            _ => return Err(invalid(self)),
+        }
+        let mut integer = true;
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if self.digits() == 0 {
+                return Err(invalid(self));
+            }
+            integer = false;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if self.digits() == 0 {
+                return Err(invalid(self));
+            }
+            integer = false;
+        }
+        let text = &self.s[start..self.pos];
+        Ok(Json::Number(if integer {
+            // We just checked the syntax, so this cannot fail.
+            JsonNumber::Integer(text.parse().unwrap())
+        } else {
+            JsonNumber::Decimal(text.to_string())
+        }))
+    }
+
+    // Four hex digits of a `\u` escape.
+    fn hex4(&mut self) -> Result<u32, ParseJsonError> {
+        let hex = self
+            .s
+            .get(self.pos..self.pos + 4)
+            .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error(JsonErrorKind::InvalidEscape))?;
+        self.pos += 4;
+        Ok(u32::from_str_radix(hex, 16).unwrap())
+    }
+
+    // `\u` escapes are UTF-16, so characters outside the basic plane come as a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char, ParseJsonError> {
+        let start = self.pos;
+        let invalid = ParseJsonError {
+            kind: JsonErrorKind::InvalidEscape,
+            offset: start,
+        };
+        let high = self.hex4()?;
+        let code = match high {
+            0xd800..=0xdbff => {
+                if !self.s[self.pos..].starts_with("\\u") {
+                    return Err(invalid);
+                }
+                self.pos += 2;
+                let low = self.hex4()?;
+                if !(0xdc00..=0xdfff).contains(&low) {
+                    return Err(invalid);
+                }
+                0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+            }
+            _ => high,
+        };
+        // A lone low surrogate is not a character.
+        ::std::char::from_u32(code).ok_or(invalid)
+    }
+
+    fn string(&mut self) -> Result<String, ParseJsonError> {
+        self.expect(b'"')?;
+        let mut result = String::new();
+        loop {
+            let c = match self.s[self.pos..].chars().next() {
+                Some(c) => c,
+                None => return Err(self.error(JsonErrorKind::UnexpectedEnd)),
+            };
+            match c {
+                '"' => {
+                    self.pos += 1;
+                    return Ok(result);
+                }
+                '\\' => {
+                    self.pos += 1;
+                    let escaped = match self.peek() {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => {
+                            self.pos += 1;
+                            result.push(self.unicode_escape()?);
+                            continue;
+                        }
+                        _ => return Err(self.error(JsonErrorKind::InvalidEscape)),
+                    };
+                    self.pos += 1;
+                    result.push(escaped);
+                }
+                // Control characters have to be escaped.
+                c if (c as u32) < 0x20 => return Err(self.unexpected()),
+                c => {
+                    self.pos += c.len_utf8();
+                    result.push(c);
+                }
+            }
+        }
+    }
+
+    // Parses a comma-separated list up to `close`, calling `item` for each element.
+    fn list<F>(&mut self, close: u8, mut item: F) -> Result<(), ParseJsonError>
+    where
+        F: FnMut(&mut Self) -> Result<(), ParseJsonError>,
+    {
+        self.pos += 1;
+        self.skip_whitespace();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(());
+        }
+        loop {
+            item(self)?;
+            self.skip_whitespace();
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b) if b == close => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Json, ParseJsonError> {
+        let mut items = vec![];
+        self.list(b']', |p| {
+            items.push(p.value()?);
+            Ok(())
+        })?;
+        Ok(Json::Array(items))
+    }
+
+    fn object(&mut self) -> Result<Json, ParseJsonError> {
+        let mut fields = vec![];
+        self.list(b'}', |p| {
+            p.skip_whitespace();
+            let key = p.string()?;
+            p.skip_whitespace();
+            p.expect(b':')?;
+            fields.push((key, p.value()?));
+            Ok(())
+        })?;
+        Ok(Json::Object(fields))
+    }
+}
+
+impl str::FromStr for Json {
+    type Err = ParseJsonError;
+    fn from_str(s: &str) -> Result<Json, ParseJsonError> {
+        let mut parser = Parser {
+            s,
+            pos: 0,
+            depth: 0,
+        };
+        let value = parser.value()?;
+        parser.skip_whitespace();
+        if parser.pos != s.len() {
+            return Err(parser.error(JsonErrorKind::TrailingCharacters));
+        }
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn json(s: &str) -> Json {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_exact_numbers() {
+        // 2^256 - 1 and a balance with more digits than an `f64` holds.
+        let max = "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+        let doc = format!(
+            r#"{{"id": {}, "balance": -1234567890.123456789012345678, "rate": 6.02E23}}"#,
+            max
+        );
+        let v = json(&doc);
+        assert_eq!(v.get("id").unwrap().as_integer().unwrap().to_string(), max);
+        // `None` if the value is not a number, or too large for a `BigDecimal`.
+        let decimal = |v: &Json| match v {
+            Json::Number(n) => n.to_decimal().map(|d| d.to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            decimal(v.get("balance").unwrap()).as_deref(),
+            Some("-1234567890.123456789012345678")
+        );
+        assert_eq!(
+            decimal(v.get("rate").unwrap()).as_deref(),
+            Some("602000000000000000000000")
+        );
+        for huge in [
+            "1e-9223372036854775808",
+            "1e9223372036854775807",
+            "1e100001",
+        ] {
+            assert!(matches!(json(huge), Json::Number(_)));
+            assert_eq!(decimal(&json(huge)), None);
+        }
+        assert_eq!(
+            v.to_string(),
+            format!(
+                r#"{{"id":{},"balance":-1234567890.123456789012345678,"rate":6.02E23}}"#,
+                max
+            )
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let text = r#"[null,true,false,"a\"b\\c\n\u0001😀",{},[],{"x":[1,-2,0.5e-3]}]"#;
+        let v = json(text);
+        assert_eq!(v.to_string(), text);
+        assert_eq!(json(r#""\ud83d\ude00""#), Json::from("\u{1f600}"));
+        assert_eq!(json(&v.to_string()), v);
+        assert_eq!(
+            json(" { \"k\" : 1 , \"k\" : 2 } ").get("k"),
+            Some(&json("2"))
+        );
+
+        let built = Json::Array(vec![
+            Json::from(BigInt::from_vec(vec![0, 1])),
+            Json::from("\t"),
+            Json::from("1.50".parse::<BigDecimal>().unwrap()),
+        ]);
+        assert_eq!(built.to_string(), r#"[18446744073709551616,"\t",1.50]"#);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| s.parse::<Json>().unwrap_err();
+        let at = |kind, offset| ParseJsonError { kind, offset };
+        assert_eq!(err("01"), at(JsonErrorKind::TrailingCharacters, 1));
+        assert_eq!(err("1."), at(JsonErrorKind::InvalidNumber, 2));
+        assert_eq!(err("+1"), at(JsonErrorKind::UnexpectedChar('+'), 0));
+        assert_eq!(err("[1,]"), at(JsonErrorKind::UnexpectedChar(']'), 3));
+        assert_eq!(err(r#""\x""#), at(JsonErrorKind::InvalidEscape, 2));
+        assert_eq!(err(r#""\udc00""#), at(JsonErrorKind::InvalidEscape, 3));
+        assert_eq!(err("{\"a\" 1}"), at(JsonErrorKind::UnexpectedChar('1'), 5));
+        assert_eq!(err("[\"abc"), at(JsonErrorKind::UnexpectedEnd, 5));
+        assert_eq!(err(&"[".repeat(200)), at(JsonErrorKind::TooDeep, 128));
+        assert_eq!(
+            err("nul").to_string(),
+            "unexpected end of input at offset 3"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+// Compact Binary Encoding with LEB128
+// ===================================
+
+// A `BigInt` stores its digits in a `Vec<u64>`, so even the number 5 takes a full 8 bytes, plus
+// the length. When writing many mostly small numbers to disk, that is wasteful. LEB128 ("little
+// endian base 128"), the variable-length encoding used by DWARF, WebAssembly and Protocol Buffers,
+// does better: it cuts the number into groups of 7 bits, least significant first, and stores each
+// group in one byte. The top bit of each byte says whether more bytes follow. Numbers below 128 take
+// a single byte, and there is no upper limit.
+use part05::BigInt;
+use std::{error, fmt, io};
+
+#[derive(Debug)]
+pub enum DecodeError {
+    Io(io::Error),
+    // The input ended in the middle of a value.
+    Truncated,
+    // The value has redundant trailing zero groups, like `80 00` for zero. We reject these, so that
+    // every number has exactly one encoding.
+    Overlong,
+    // A length prefix is too large for this platform.
+    LengthOverflow,
+    // `decode` was given more bytes than the value used.
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::Io(e) => e.fmt(f),
+            DecodeError::Truncated => write!(f, "input ended in the middle of a value"),
+            DecodeError::Overlong => write!(f, "value is not minimally encoded"),
+            DecodeError::LengthOverflow => write!(f, "length prefix is too large"),
+            DecodeError::TrailingBytes => write!(f, "unexpected bytes after the value"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DecodeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Running out of input is not an I/O problem for us, but a malformed value.
+impl From<io::Error> for DecodeError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            DecodeError::Truncated
+        } else {
+            DecodeError::Io(e)
+        }
+    }
+}
+
+// ## Single numbers
+
+// The 7-bit group starting at bit `i`. It may straddle two digits.
+fn group_at(b: &BigInt, i: usize) -> u8 {
+    let (digit, shift) = (i / 64, i % 64);
+    let mut group = b.data[digit] >> shift;
+    if shift > 57 && digit + 1 < b.data.len() {
+        group |= b.data[digit + 1] << (64 - shift);
+    }
+    (group & 0x7f) as u8
+}
+
+// We write all bytes at once, so that a `Write` without buffering does not get one call per byte.
+pub fn write_bigint<W: io::Write>(w: &mut W, b: &BigInt) -> io::Result<()> {
+    let groups = b.bits().div_ceil(7).max(1);
+    let bytes: Vec<u8> = (0..groups)
+        .map(|g| {
+            let group = if b.is_zero() { 0 } else { group_at(b, 7 * g) };
+            let more = if g + 1 < groups { 0x80 } else { 0 };
+            group | more
+        })
+        .collect();
+    w.write_all(&bytes)
+}
+
+// Reads one number. This reads a byte at a time, so for files, wrap the reader in an
+// `io::BufReader`.
+pub fn read_bigint<R: io::Read>(r: &mut R) -> Result<BigInt, DecodeError> {
+    let mut data: Vec<u64> = vec![];
+    let mut bit = 0;
+    loop {
+        let mut byte = [0u8];
+        r.read_exact(&mut byte)?;
+        let (group, more) = ((byte[0] & 0x7f) as u64, byte[0] & 0x80 != 0);
+        let (digit, shift) = (bit / 64, bit % 64);
+        if digit == data.len() {
+            data.push(0);
+        }
+        data[digit] |= group << shift;
+        if shift > 57 && group >> (64 - shift) != 0 {
+            data.push(group >> (64 - shift));
+        }
+        if !more {
+            // A final zero group is only allowed as the encoding of zero itself.
+            if group == 0 && bit > 0 {
+                return Err(DecodeError::Overlong);
+            }
+            return Ok(BigInt::from_vec(data));
+        }
+        bit += 7;
+    }
+}
+
+// Convenience functions for a single number in memory.
+pub fn encode(b: &BigInt) -> Vec<u8> {
+    let mut bytes = vec![];
+    // Writing to a `Vec` cannot fail.
+    write_bigint(&mut bytes, b).unwrap();
+    bytes
+}
+
+pub fn decode(mut bytes: &[u8]) -> Result<BigInt, DecodeError> {
+    let b = read_bigint(&mut bytes)?;
+    if !bytes.is_empty() {
+        return Err(DecodeError::TrailingBytes);
+    }
+    Ok(b)
+}
+
+// ## Sequences
+
+// A sequence is its length, in LEB128 as well, followed by the numbers.
+pub fn write_vec<W: io::Write>(w: &mut W, v: &[BigInt]) -> io::Result<()> {
+    write_bigint(w, &BigInt::new(v.len() as u64))?;
+    for b in v {
+        write_bigint(w, b)?;
+    }
+    Ok(())
+}
+
+pub fn read_vec<R: io::Read>(r: &mut R) -> Result<Vec<BigInt>, DecodeError> {
+    let len = read_bigint(r)?
+        .to_u64()
+        .filter(|&len| len <= usize::MAX as u64)
+        .ok_or(DecodeError::LengthOverflow)? as usize;
+    // The length comes from the input, so we do not trust it for preallocation: a corrupted
+    // prefix should give `Truncated`, not an out-of-memory abort.
+    let mut v = Vec::with_capacity(len.min(1024));
+    for _ in 0..len {
+        v.push(read_bigint(r)?);
+    }
+    Ok(v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_encodings() {
+        assert_eq!(encode(&BigInt::zero()), vec![0x00]);
+        assert_eq!(encode(&BigInt::new(127)), vec![0x7f]);
+        assert_eq!(encode(&BigInt::new(128)), vec![0x80, 0x01]);
+        assert_eq!(encode(&BigInt::new(624_485)), vec![0xe5, 0x8e, 0x26]);
+        let mut two_64 = vec![0x80; 9];
+        two_64.push(0x02);
+        assert_eq!(encode(&BigInt::from_vec(vec![0, 1])), two_64);
+        assert_eq!(decode(&two_64).unwrap(), BigInt::from_vec(vec![0, 1]));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut values = vec![BigInt::zero(), BigInt::new(u64::MAX)];
+        let mut b = BigInt::new(3);
+        for _ in 0..40 {
+            b = &b * &BigInt::new(0x1234_5678_9abc_def1);
+            values.push(b.clone());
+        }
+        for v in &values {
+            assert_eq!(decode(&encode(v)).unwrap(), *v);
+        }
+        let mut bytes = vec![];
+        write_vec(&mut bytes, &values).unwrap();
+        assert_eq!(read_vec(&mut io::Cursor::new(&bytes)).unwrap(), values);
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |bytes: &[u8]| decode(bytes).unwrap_err().to_string();
+        assert_eq!(err(&[]), "input ended in the middle of a value");
+        assert_eq!(err(&[0xe5, 0x8e]), "input ended in the middle of a value");
+        assert_eq!(err(&[0x80, 0x00]), "value is not minimally encoded");
+        assert_eq!(err(&[0x01, 0x02]), "unexpected bytes after the value");
+        // A length prefix of 2^64 does not fit, and a length of 3 with only two values is cut off.
+        let mut huge = vec![0x80; 9];
+        huge.push(0x02);
+        assert!(matches!(
+            read_vec(&mut &huge[..]),
+            Err(DecodeError::LengthOverflow)
+        ));
+        assert!(matches!(
+            read_vec(&mut &[3, 1, 2][..]),
+            Err(DecodeError::Truncated)
+        ));
+    }
+}
@@ -24,6 +24,35 @@ mod part14;
 mod part15;
 mod part16;
 
+// The following modules go beyond the tutorial, and build number types on top of `BigInt`.
+// `arith` has to come first, since it defines a macro the others use.
+#[macro_use]
+mod arith;
+mod bigdecimal;
+mod bigint_ref;
+mod constants;
+mod ct;
+mod ec;
+mod extrema;
+mod float;
+mod input;
+mod json;
+mod leb128;
+mod matrix;
+mod monoid;
+mod modint;
+mod number_theory;
+mod numeric;
+mod polynomial;
+mod random;
+mod rational;
+mod rsa;
+mod shared;
+mod signed;
+mod statistics;
+mod streaming;
+mod summation;
+
 // This decides which part is actually run.
 fn main() {
    part15::main();
@@ -0,0 +1,286 @@
+// Exact Integer Matrices
+// ======================
+
+// Floating-point elimination rounds at every step, so determinants of integer matrices come out
+// as `41.99999`. With big integer entries, we can keep everything exact, as long as we never
+// divide unless we know the division is exact.
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::{fmt, ops};
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<SignedBigInt>, // row by row
+}
+
+impl Matrix {
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Matrix {
+            rows,
+            cols,
+            data: vec![SignedBigInt::zero(); rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Self {
+        let mut m = Matrix::zero(n, n);
+        for i in 0..n {
+            m[(i, i)] = SignedBigInt::one();
+        }
+        m
+    }
+
+    // Builds a matrix from its rows. Returns `None` if they do not all have the same length.
+    pub fn from_rows(rows: Vec<Vec<SignedBigInt>>) -> Option<Self> {
+        let cols = rows.first().map(|r| r.len()).unwrap_or(0);
+        if rows.iter().any(|r| r.len() != cols) {
+            return None;
+        }
+        Some(Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+
+    // Exponentiation by squaring, as for `BigInt::pow`. With the matrix `[[1, 1], [1, 0]]`, this
+    // computes Fibonacci numbers in a logarithmic number of multiplications. Returns `None` for
+    // non-square matrices.
+    pub fn pow(&self, mut exp: u64) -> Option<Matrix> {
+        if !self.is_square() {
+            return None;
+        }
+        let mut base = self.clone();
+        let mut result = Matrix::identity(self.rows);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = (&result * &base)?;
+            }
+            exp >>= 1;
+            if exp > 0 {
+                base = (&base * &base)?;
+            }
+        }
+        Some(result)
+    }
+
+    // Computes the determinant with the Bareiss algorithm. It is Gaussian elimination, except that
+    // instead of dividing by the pivot, we cross-multiply and then divide by the *previous* pivot.
+    // That division is always exact (the entries are minors of the input), so the numbers stay
+    // integers and grow only linearly. Returns `None` for non-square matrices.
+    pub fn determinant(&self) -> Option<SignedBigInt> {
+        if !self.is_square() {
+            return None;
+        }
+        let n = self.rows;
+        let mut m = self.clone();
+        let mut negate = false;
+        let mut prev = SignedBigInt::one();
+        for k in 0..n {
+            // Find a non-zero pivot. Each row swap flips the sign of the determinant.
+            let pivot = match (k..n).find(|&i| !m[(i, k)].is_zero()) {
+                Some(i) => i,
+                None => return Some(SignedBigInt::zero()),
+            };
+            if pivot != k {
+                m.swap_rows(pivot, k);
+                negate = !negate;
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let t = &m[(i, j)] * &m[(k, k)] - &m[(i, k)] * &m[(k, j)];
+                    m[(i, j)] = &t / &prev;
+                }
+            }
+            prev = m[(k, k)].clone();
+        }
+        // For the empty matrix, `prev` is still 1, which is the right answer.
+        Some(if negate { -prev } else { prev })
+    }
+
+    // The Hermite normal form `H = U * self` for a unimodular `U`: `H` is in row echelon form, the
+    // pivots are positive, and the entries above each pivot are reduced into `0..pivot`. Since
+    // `U` is invertible over the integers, `H` spans the same lattice as the rows of `self`.
+    pub fn hermite_normal_form(&self) -> Matrix {
+        let mut h = self.clone();
+        let mut r = 0;
+        for col in 0..h.cols {
+            if r == h.rows {
+                break;
+            }
+            // Clear the column below row `r`. For each non-zero entry `b` we replace the rows by
+            // `(s * row_r + t * row_i, (-b/g) * row_r + (a/g) * row_i)`, where `s*a + t*b = g`.
+            // That matrix has determinant 1, so it is unimodular.
+            for i in r + 1..h.rows {
+                if h[(i, col)].is_zero() {
+                    continue;
+                }
+                let a = h[(r, col)].clone();
+                let b = h[(i, col)].clone();
+                let (g, s, t) = a.extended_gcd(&b);
+                let g = SignedBigInt::from(g);
+                let (a_g, b_g) = (&a / &g, &b / &g);
+                for j in col..h.cols {
+                    let (x, y) = (h[(r, j)].clone(), h[(i, j)].clone());
+                    h[(r, j)] = &s * &x + &t * &y;
+                    h[(i, j)] = &a_g * &y - &b_g * &x;
+                }
+            }
+            if h[(r, col)].is_zero() {
+                continue;
+            }
+            if h[(r, col)].is_negative() {
+                for j in col..h.cols {
+                    h[(r, j)] = -&h[(r, j)];
+                }
+            }
+            // Reduce the entries above the pivot.
+            let pivot = h[(r, col)].magnitude().clone();
+            for i in 0..r {
+                let q = h[(i, col)].div_rem_euclid(&pivot).unwrap().0;
+                for j in col..h.cols {
+                    h[(i, j)] = &h[(i, j)] - &(&q * &h[(r, j)]);
+                }
+            }
+            r += 1;
+        }
+        h
+    }
+
+    // The rank is the number of non-zero rows in the Hermite normal form.
+    pub fn rank(&self) -> usize {
+        let h = self.hermite_normal_form();
+        (0..h.rows)
+            .filter(|&i| (0..h.cols).any(|j| !h[(i, j)].is_zero()))
+            .count()
+    }
+}
+
+impl ops::Index<(usize, usize)> for Matrix {
+    type Output = SignedBigInt;
+    fn index(&self, (i, j): (usize, usize)) -> &SignedBigInt {
+        assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut SignedBigInt {
+        assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+// Like subtraction of `BigInt`s in part 08, multiplication is not always defined. We return `None`
+// if the dimensions do not fit.
+impl ops::Mul<&Matrix> for &Matrix {
+    type Output = Option<Matrix>;
+    fn mul(self, rhs: &Matrix) -> Option<Matrix> {
+        if self.cols != rhs.rows {
+            return None;
+        }
+        let mut result = Matrix::zero(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for j in 0..rhs.cols {
+                let mut sum = SignedBigInt::zero();
+                for k in 0..self.cols {
+                    sum = sum + &self[(i, k)] * &rhs[(k, j)];
+                }
+                result[(i, j)] = sum;
+            }
+        }
+        Some(result)
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.rows {
+            let row: Vec<String> = (0..self.cols).map(|j| self[(i, j)].to_string()).collect();
+            writeln!(f, "[{}]", row.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mat(rows: &[&[i64]]) -> Matrix {
+        let rows = rows
+            .iter()
+            .map(|r| r.iter().map(|&x| SignedBigInt::from(x)).collect())
+            .collect();
+        Matrix::from_rows(rows).unwrap()
+    }
+
+    #[test]
+    fn test_mul_pow() {
+        let a = mat(&[&[1, 2], &[3, 4]]);
+        let b = mat(&[&[0, 1], &[1, 0]]);
+        assert_eq!((&a * &b).unwrap(), mat(&[&[2, 1], &[4, 3]]));
+        assert_eq!(&a * &mat(&[&[1, 2, 3]]), None);
+
+        let fib = mat(&[&[1, 1], &[1, 0]]).pow(100).unwrap();
+        assert_eq!(fib[(0, 1)].to_string(), "354224848179261915075");
+        assert_eq!(a.pow(0), Some(Matrix::identity(2)));
+    }
+
+    #[test]
+    fn test_determinant() {
+        assert_eq!(
+            mat(&[&[2, -3, 1], &[2, 0, -1], &[1, 4, 5]]).determinant(),
+            Some(SignedBigInt::from(49))
+        );
+        // The first pivot is zero, so the rows have to be swapped.
+        assert_eq!(
+            mat(&[&[0, 1], &[1, 0]]).determinant(),
+            Some(SignedBigInt::from(-1))
+        );
+        assert_eq!(
+            mat(&[&[1, 2], &[2, 4]]).determinant(),
+            Some(SignedBigInt::zero())
+        );
+        assert_eq!(mat(&[&[1, 2]]).determinant(), None);
+    }
+
+    #[test]
+    fn test_hnf_rank() {
+        let a = mat(&[&[2, 3, 6, 2], &[5, 6, 1, 6], &[8, 3, 1, 1]]);
+        let h = a.hermite_normal_form();
+        assert_eq!(
+            h,
+            mat(&[&[1, 0, 50, -11], &[0, 3, 28, -2], &[0, 0, 61, -13]])
+        );
+        assert_eq!(a.rank(), 3);
+        assert_eq!(mat(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]).rank(), 2);
+        assert_eq!(mat(&[&[0, 0], &[0, 0]]).rank(), 0);
+    }
+}
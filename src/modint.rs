@@ -0,0 +1,327 @@
+// Modular Integers and the Chinese Remainder Theorem
+// ==================================================
+
+// Code doing modular arithmetic tends to end every line with `% m`, and forgetting one is a bug
+// that only shows for large inputs. A residue type that carries its modulus does the reduction in
+// the operators instead.
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::{fmt, ops};
+
+// ## Runtime modulus
+
+// A residue modulo a `BigInt` chosen at runtime. The value is always reduced into `0..modulus`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ModInt {
+    value: BigInt,
+    modulus: BigInt,
+}
+
+impl ModInt {
+    // Panics if the modulus is zero, just like `%` would.
+    pub fn new(value: &BigInt, modulus: &BigInt) -> Self {
+        ModInt {
+            value: value % modulus,
+            modulus: modulus.clone(),
+        }
+    }
+
+    // Negative values are reduced to their non-negative representative, so `-1 (mod 7)` is `6`.
+    pub fn from_signed(value: &SignedBigInt, modulus: &BigInt) -> Self {
+        ModInt {
+            value: value.rem_euclid(modulus),
+            modulus: modulus.clone(),
+        }
+    }
+
+    pub fn value(&self) -> &BigInt {
+        &self.value
+    }
+
+    pub fn modulus(&self) -> &BigInt {
+        &self.modulus
+    }
+
+    pub fn pow(&self, exp: &BigInt) -> ModInt {
+        ModInt {
+            value: self.value.mod_pow(exp, &self.modulus),
+            modulus: self.modulus.clone(),
+        }
+    }
+
+    // Returns `None` if the value is not coprime to the modulus.
+    pub fn inverse(&self) -> Option<ModInt> {
+        Some(ModInt {
+            value: self.value.mod_inverse(&self.modulus)?,
+            modulus: self.modulus.clone(),
+        })
+    }
+
+    // Division multiplies with the inverse. Returns `None` if there is none.
+    pub fn checked_div(&self, rhs: &ModInt) -> Option<ModInt> {
+        self.check_modulus(rhs);
+        Some(self * &rhs.inverse()?)
+    }
+
+    // Combining residues with different moduli is a bug, so we make it loud.
+    fn check_modulus(&self, other: &ModInt) {
+        assert!(self.modulus == other.modulus, "moduli differ");
+    }
+}
+
+impl ops::Add<&ModInt> for &ModInt {
+    type Output = ModInt;
+    fn add(self, rhs: &ModInt) -> ModInt {
+        self.check_modulus(rhs);
+        ModInt::new(&(&self.value + &rhs.value), &self.modulus)
+    }
+}
+
+impl ops::Sub<&ModInt> for &ModInt {
+    type Output = ModInt;
+    fn sub(self, rhs: &ModInt) -> ModInt {
+        self.check_modulus(rhs);
+        // Adding the modulus first keeps the `BigInt` subtraction from going negative.
+        let diff = (&(&self.value + &self.modulus) - &rhs.value).unwrap();
+        ModInt::new(&diff, &self.modulus)
+    }
+}
+
+impl ops::Mul<&ModInt> for &ModInt {
+    type Output = ModInt;
+    fn mul(self, rhs: &ModInt) -> ModInt {
+        self.check_modulus(rhs);
+        ModInt::new(&(&self.value * &rhs.value), &self.modulus)
+    }
+}
+
+// Like the integer types, the operator panics if the division is undefined.
+impl ops::Div<&ModInt> for &ModInt {
+    type Output = ModInt;
+    fn div(self, rhs: &ModInt) -> ModInt {
+        self.checked_div(rhs).expect("divisor is not invertible")
+    }
+}
+
+forward_binop!(ModInt, Add, add);
+forward_binop!(ModInt, Sub, sub);
+forward_binop!(ModInt, Mul, mul);
+forward_binop!(ModInt, Div, div);
+
+impl ops::Neg for &ModInt {
+    type Output = ModInt;
+    fn neg(self) -> ModInt {
+        let zero = ModInt::new(&BigInt::zero(), &self.modulus);
+        &zero - self
+    }
+}
+
+impl ops::Neg for ModInt {
+    type Output = ModInt;
+    fn neg(self) -> ModInt {
+        -&self
+    }
+}
+
+impl fmt::Display for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, self.modulus)
+    }
+}
+
+impl fmt::Debug for ModInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+// ## Chinese Remainder Theorem
+
+// Finds the `x` with `x = r (mod m)` for all given residues. The result is unique modulo the lcm
+// of the moduli, so we return it as a residue of that. The moduli do not have to be coprime; if
+// they are not, the system may have no solution, and we return `None`.
+pub fn crt(residues: &[ModInt]) -> Option<ModInt> {
+    let mut acc = ModInt::new(&BigInt::zero(), &BigInt::one());
+    for r in residues {
+        acc = crt_pair(&acc, r)?;
+    }
+    Some(acc)
+}
+
+// Merges `x = a1 (mod m1)` and `x = a2 (mod m2)`. With `g = gcd(m1, m2)`, there is a solution iff
+// `a2 - a1` is divisible by `g`. Then `x = a1 + m1 * k`, where `k` solves
+// `(m1/g) * k = (a2 - a1)/g (mod m2/g)`.
+fn crt_pair(a: &ModInt, b: &ModInt) -> Option<ModInt> {
+    let (a1, m1) = (&a.value, &a.modulus);
+    let (a2, m2) = (&b.value, &b.modulus);
+    let g = m1.gcd(m2);
+    let diff = SignedBigInt::from(a2.clone()) - SignedBigInt::from(a1.clone());
+    let (q, r) = diff.div_rem_euclid(&g).unwrap();
+    if !r.is_zero() {
+        return None;
+    }
+    let m2_g = m2 / &g;
+    // `m1/g` and `m2/g` are coprime, so the inverse exists.
+    let inv = (m1 / &g).mod_inverse(&m2_g).unwrap();
+    let k = &(&q.rem_euclid(&m2_g) * &inv) % &m2_g;
+    let lcm = m1 * &m2_g;
+    Some(ModInt::new(&(a1 + &(m1 * &k)), &lcm))
+}
+
+// ## Compile-time modulus
+
+// For moduli that fit into a machine word and are known in advance (like the popular
+// `998244353`), we can make the modulus part of the type and compute with `u128` intermediates.
+// Mixing residues of different moduli then becomes a type error instead of a panic.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct ConstModInt<const M: u64> {
+    value: u64,
+}
+
+impl<const M: u64> ConstModInt<M> {
+    pub fn new(value: u64) -> Self {
+        assert!(
+            M != 0,
+            "attempt to calculate the remainder with a divisor of zero"
+        );
+        ConstModInt { value: value % M }
+    }
+
+    pub fn value(self) -> u64 {
+        self.value
+    }
+
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut result = ConstModInt::new(1);
+        while exp > 0 {
+            if exp & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        result
+    }
+
+    pub fn inverse(self) -> Option<Self> {
+        let inv = BigInt::new(self.value).mod_inverse(&BigInt::new(M))?;
+        Some(ConstModInt::new(inv.to_u64().unwrap()))
+    }
+}
+
+impl<const M: u64> From<u64> for ConstModInt<M> {
+    fn from(value: u64) -> Self {
+        ConstModInt::new(value)
+    }
+}
+
+impl<const M: u64> ops::Add for ConstModInt<M> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        ConstModInt {
+            value: ((self.value as u128 + rhs.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> ops::Sub for ConstModInt<M> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        ConstModInt {
+            value: ((self.value as u128 + M as u128 - rhs.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> ops::Mul for ConstModInt<M> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        ConstModInt {
+            value: ((self.value as u128 * rhs.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> ops::Div for ConstModInt<M> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let inv = rhs.inverse().expect("divisor is not invertible");
+        ConstModInt {
+            value: ((self.value as u128 * inv.value as u128) % M as u128) as u64,
+        }
+    }
+}
+
+impl<const M: u64> ops::Neg for ConstModInt<M> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        ConstModInt::new(0) - self
+    }
+}
+
+impl<const M: u64> fmt::Display for ConstModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (mod {})", self.value, M)
+    }
+}
+
+impl<const M: u64> fmt::Debug for ConstModInt<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn m(value: u64, modulus: u64) -> ModInt {
+        ModInt::new(&BigInt::new(value), &BigInt::new(modulus))
+    }
+
+    #[test]
+    fn test_modint() {
+        let a = m(5, 7);
+        let b = m(4, 7);
+        assert_eq!(&a + &b, m(2, 7));
+        assert_eq!(&b - &a, m(6, 7));
+        assert_eq!(&a * &b, m(6, 7));
+        assert_eq!(&(&a / &b) * &b, a);
+        assert_eq!(-m(1, 7), m(6, 7));
+        assert_eq!(m(3, 7).pow(&BigInt::new(6)), m(1, 7));
+        assert_eq!(m(2, 8).inverse(), None);
+        assert_eq!(
+            ModInt::from_signed(&SignedBigInt::from(-1), &BigInt::new(7)),
+            m(6, 7)
+        );
+        assert_eq!(a.to_string(), "5 (mod 7)");
+    }
+
+    #[test]
+    #[should_panic(expected = "moduli differ")]
+    fn test_modint_mismatch() {
+        let _ = m(1, 7) + m(1, 5);
+    }
+
+    #[test]
+    fn test_crt() {
+        // The classic from Sunzi: x = 2 (mod 3), x = 3 (mod 5), x = 2 (mod 7).
+        assert_eq!(crt(&[m(2, 3), m(3, 5), m(2, 7)]), Some(m(23, 105)));
+        // Non-coprime moduli: x = 2 (mod 4) and x = 4 (mod 6) give x = 10 (mod 12).
+        assert_eq!(crt(&[m(2, 4), m(4, 6)]), Some(m(10, 12)));
+        assert_eq!(crt(&[m(1, 4), m(2, 6)]), None);
+        assert_eq!(crt(&[]), Some(m(0, 1)));
+    }
+
+    #[test]
+    fn test_const_modint() {
+        type F = ConstModInt<998_244_353>;
+        let a = F::new(998_244_352);
+        assert_eq!((a + F::new(2)).value(), 1);
+        assert_eq!((F::new(1) - F::new(2)).value(), 998_244_352);
+        assert_eq!(F::new(3).pow(998_244_352).value(), 1);
+        assert_eq!((F::new(10) / F::new(4) * F::new(4)).value(), 10);
+        assert_eq!(-F::new(0), F::new(0));
+    }
+}
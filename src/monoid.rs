@@ -0,0 +1,252 @@
+// Reductions with Monoids
+// =======================
+
+// `vec_min` in part 00, `vec_sum` in part 01 and the generic `vec_min` in part 02 are the same loop:
+// start with "nothing", and combine the elements one by one. What changes is only how two values
+// are combined. A *semigroup* is a type with such an associative `combine`; a *monoid* in addition
+// has an `empty` value that changes nothing when combined. With those, the loop is written once,
+// and since combining pairs of values gives again a value of the same kind, several reductions can
+// run in a single pass, and a long input can be cut into chunks that are reduced in parallel.
+use numeric::{One, Zero};
+use std::{ops, thread};
+
+// ## The traits
+
+// `combine` has to be associative: `a.combine(b).combine(c) == a.combine(b.combine(c))`. It need
+// not be commutative, and `First` and `Last` are not.
+pub trait Semigroup: Sized {
+    fn combine(self, other: Self) -> Self;
+}
+
+// `empty` has to be neutral: `empty().combine(a) == a == a.combine(empty())`.
+pub trait Monoid: Semigroup {
+    fn empty() -> Self;
+}
+
+// Any semigroup becomes a monoid by adding a "nothing" for it to start from, which is exactly the
+// `SomethingOrNothing` trick of part 02. `None` is the result of reducing nothing.
+impl<S: Semigroup> Semigroup for Option<S> {
+    fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Some(a), Some(b)) => Some(a.combine(b)),
+            (a, None) => a,
+            (None, b) => b,
+        }
+    }
+}
+
+impl<S: Semigroup> Monoid for Option<S> {
+    fn empty() -> Self {
+        None
+    }
+}
+
+// ## Instances
+
+// Most types can be combined in more than one way, so we say which one we mean with a wrapper.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Min<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Max<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sum<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Product<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct First<T>(pub T);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Last<T>(pub T);
+
+// On ties, and for unordered values like NaN, the left value stays. `Min` and `Max` have no
+// neutral element in general (what would it be for `BigInt`?), so wrap them in an `Option`.
+impl<T: PartialOrd> Semigroup for Min<T> {
+    fn combine(self, other: Self) -> Self {
+        if other.0 < self.0 {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<T: PartialOrd> Semigroup for Max<T> {
+    fn combine(self, other: Self) -> Self {
+        if other.0 > self.0 {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<T: ops::Add<Output = T>> Semigroup for Sum<T> {
+    fn combine(self, other: Self) -> Self {
+        Sum(self.0 + other.0)
+    }
+}
+
+impl<T: ops::Add<Output = T> + Zero> Monoid for Sum<T> {
+    fn empty() -> Self {
+        Sum(T::zero())
+    }
+}
+
+impl<T: ops::Mul<Output = T>> Semigroup for Product<T> {
+    fn combine(self, other: Self) -> Self {
+        Product(self.0 * other.0)
+    }
+}
+
+impl<T: ops::Mul<Output = T> + One> Monoid for Product<T> {
+    fn empty() -> Self {
+        Product(T::one())
+    }
+}
+
+impl<T> Semigroup for First<T> {
+    fn combine(self, _other: Self) -> Self {
+        self
+    }
+}
+
+impl<T> Semigroup for Last<T> {
+    fn combine(self, other: Self) -> Self {
+        other
+    }
+}
+
+// Pairs and triples are combined component-wise. This is what lets us compute several results in
+// one pass.
+impl<A: Semigroup, B: Semigroup> Semigroup for (A, B) {
+    fn combine(self, other: Self) -> Self {
+        (self.0.combine(other.0), self.1.combine(other.1))
+    }
+}
+
+impl<A: Monoid, B: Monoid> Monoid for (A, B) {
+    fn empty() -> Self {
+        (A::empty(), B::empty())
+    }
+}
+
+impl<A: Semigroup, B: Semigroup, C: Semigroup> Semigroup for (A, B, C) {
+    fn combine(self, other: Self) -> Self {
+        (
+            self.0.combine(other.0),
+            self.1.combine(other.1),
+            self.2.combine(other.2),
+        )
+    }
+}
+
+impl<A: Monoid, B: Monoid, C: Monoid> Monoid for (A, B, C) {
+    fn empty() -> Self {
+        (A::empty(), B::empty(), C::empty())
+    }
+}
+
+// ## Reducing
+
+// Combines all elements from left to right, or returns `None` if there are none.
+pub fn reduce<S: Semigroup, I: IntoIterator<Item = S>>(iter: I) -> Option<S> {
+    let mut iter = iter.into_iter();
+    let first = iter.next()?;
+    Some(iter.fold(first, Semigroup::combine))
+}
+
+// Maps every element into a monoid, and combines the results. For an empty input, that is `empty`.
+pub fn fold_map<M, I, F>(iter: I, mut f: F) -> M
+where
+    M: Monoid,
+    I: IntoIterator,
+    F: FnMut(I::Item) -> M,
+{
+    iter.into_iter()
+        .fold(M::empty(), |acc, x| acc.combine(f(x)))
+}
+
+// Like `fold_map` over a slice, but split into up to `threads` chunks that are reduced on their own
+// threads. The chunk results are combined in order, so this relies on associativity only, and gives
+// the same result as `fold_map` also for `First` and `Last`.
+pub fn par_fold_map<T, M, F>(v: &[T], threads: usize, f: F) -> M
+where
+    T: Sync,
+    M: Monoid + Send,
+    F: Fn(&T) -> M + Sync,
+{
+    if v.is_empty() {
+        return M::empty();
+    }
+    let chunk_size = v.len().div_ceil(threads.max(1));
+    let f = &f;
+    thread::scope(|s| {
+        let handles: Vec<_> = v
+            .chunks(chunk_size)
+            .map(|chunk| s.spawn(move || fold_map(chunk, f)))
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .fold(M::empty(), Semigroup::combine)
+    })
+}
+
+// The same for any iterator. An iterator cannot be split into chunks up front, so we collect it
+// first; the reductions themselves still run in parallel.
+pub fn par_fold_map_iter<I, M, F>(iter: I, threads: usize, f: F) -> M
+where
+    I: IntoIterator,
+    I::Item: Sync,
+    M: Monoid + Send,
+    F: Fn(&I::Item) -> M + Sync,
+{
+    let v: Vec<I::Item> = iter.into_iter().collect();
+    par_fold_map(&v, threads, f)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use part05::BigInt;
+
+    #[test]
+    fn test_instances() {
+        assert_eq!(reduce(vec![3, 1, 2].into_iter().map(Min)), Some(Min(1)));
+        assert_eq!(reduce(vec![3, 1, 2].into_iter().map(Max)), Some(Max(3)));
+        assert_eq!(reduce(Vec::<Min<i32>>::new()), None);
+        assert_eq!(fold_map(vec![1, 2, 3, 4], Product), Product(24));
+        assert_eq!(fold_map(Vec::<f64>::new(), Sum), Sum(0.0));
+        assert_eq!(
+            fold_map(vec!['a', 'b'], |c| Some(First(c))),
+            Some(First('a'))
+        );
+        assert_eq!(fold_map(vec!['a', 'b'], |c| Some(Last(c))), Some(Last('b')));
+        let big = fold_map(vec![u64::MAX, u64::MAX], |x| Sum(BigInt::new(x)));
+        assert_eq!(big, Sum(BigInt::from_vec(vec![u64::MAX - 1, 1])));
+    }
+
+    #[test]
+    fn test_one_pass() {
+        let v = vec![18, 5, 7, 3, 9, 27];
+        let (min, max, sum) = fold_map(&v, |&x| (Some(Min(x)), Some(Max(x)), Sum(x)));
+        assert_eq!((min, max, sum), (Some(Min(3)), Some(Max(27)), Sum(69)));
+    }
+
+    #[test]
+    fn test_parallel() {
+        let v: Vec<u64> = (1..=1000).collect();
+        let f = |&x: &u64| (Sum(x), Some(First(x)));
+        let expected = fold_map(&v, f);
+        assert_eq!(expected, (Sum(500_500), Some(First(1))));
+        for threads in [1, 3, 7, 2000] {
+            assert_eq!(par_fold_map(&v, threads, f), expected);
+        }
+        assert_eq!(par_fold_map(&[] as &[u64], 4, f), (Sum(0), None));
+        assert_eq!(par_fold_map_iter(1..=1000u64, 4, f), expected);
+    }
+}
@@ -0,0 +1,433 @@
+// Number Theory on BigInt
+// =======================
+
+// Modular arithmetic is where big integers really shine. This module collects the algorithms that
+// the modular types and the cryptography code build on.
+use part05::BigInt;
+use signed::SignedBigInt;
+
+impl BigInt {
+    // Computes `self^exp mod m` by square-and-multiply, reducing after every step so the numbers
+    // never grow beyond `m^2`.
+    pub fn mod_pow(&self, exp: &BigInt, m: &BigInt) -> BigInt {
+        assert!(
+            !m.is_zero(),
+            "attempt to calculate the remainder with a divisor of zero"
+        );
+        let mut result = BigInt::one() % m;
+        let base = self % m;
+        // We go through the bits of the exponent from the top.
+        for i in (0..exp.bits()).rev() {
+            result = &(&result * &result) % m;
+            if exp.bit(i) {
+                result = &(&result * &base) % m;
+            }
+        }
+        result
+    }
+
+    // The inverse of `self` modulo `m`, i.e., the `x` with `self * x = 1 (mod m)`. It exists if and
+    // only if `self` and `m` are coprime.
+    pub fn mod_inverse(&self, m: &BigInt) -> Option<BigInt> {
+        if m.is_zero() {
+            return None;
+        }
+        let a = SignedBigInt::from(self % m);
+        let (g, s, _) = a.extended_gcd(&SignedBigInt::from(m.clone()));
+        if g != BigInt::one() {
+            return None;
+        }
+        Some(s.rem_euclid(m))
+    }
+
+    // The Miller-Rabin test. Write `self - 1 = d * 2^s` with `d` odd. For a prime `p`, every base
+    // `a` satisfies `a^d = 1` or `a^(d * 2^r) = -1 (mod p)` for some `r < s`. A base violating this
+    // proves that `self` is composite.
+    //
+    // We try the first twelve primes as bases. That is known to be exact below `3.18 * 10^23`, and
+    // for larger numbers a composite slipping through is astronomically unlikely. Trial division
+    // by the same primes first weeds out most candidates cheaply.
+    pub fn is_probable_prime(&self) -> bool {
+        const SMALL_PRIMES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+        for &p in SMALL_PRIMES.iter() {
+            let (_, r) = self.div_rem_u64(p);
+            if r == 0 {
+                return *self == BigInt::new(p);
+            }
+        }
+        if *self < BigInt::new(2) {
+            return false;
+        }
+        let one = BigInt::one();
+        let n_1 = (self - &one).unwrap();
+        let s = (0..).find(|&i| n_1.bit(i)).unwrap();
+        let d = &n_1 >> s;
+        'bases: for &a in SMALL_PRIMES.iter() {
+            let mut x = BigInt::new(a).mod_pow(&d, self);
+            if x == one || x == n_1 {
+                continue;
+            }
+            for _ in 1..s {
+                x = &(&x * &x) % self;
+                if x == n_1 {
+                    continue 'bases;
+                }
+            }
+            return false;
+        }
+        true
+    }
+}
+
+// ## Quadratic residues
+
+// The lowest bits of a number, for the `mod 4` and `mod 8` tests below.
+fn low_bits(n: &BigInt, bits: u32) -> u64 {
+    n.data.first().map(|d| d & ((1 << bits) - 1)).unwrap_or(0)
+}
+
+impl BigInt {
+    // The Jacobi symbol `(self/n)`, for odd `n`; `None` if `n` is even. It is `0` if the two share
+    // a factor, and otherwise `1` or `-1`. We compute it like the gcd, using quadratic reciprocity
+    // to swap the arguments and the rules for `(2/n)` to remove factors of two.
+    pub fn jacobi(&self, n: &BigInt) -> Option<i32> {
+        if n.is_even() {
+            return None;
+        }
+        let mut a = self % n;
+        let mut n = n.clone();
+        let mut result = 1;
+        while !a.is_zero() {
+            let twos = (0..).find(|&i| a.bit(i)).unwrap();
+            a = &a >> twos;
+            // `(2/n)` is `-1` iff `n = 3` or `5 (mod 8)`.
+            if twos % 2 == 1 && (low_bits(&n, 3) == 3 || low_bits(&n, 3) == 5) {
+                result = -result;
+            }
+            // Reciprocity: `(a/n) = -(n/a)` iff both are `3 (mod 4)`.
+            if low_bits(&a, 2) == 3 && low_bits(&n, 2) == 3 {
+                result = -result;
+            }
+            let r = &n % &a;
+            n = a;
+            a = r;
+        }
+        Some(if n == BigInt::one() { result } else { 0 })
+    }
+
+    // The Legendre symbol `(self/p)`: `1` if `self` is a non-zero square modulo the prime `p`,
+    // `-1` if it is not a square, and `0` if it is divisible by `p`. For primes, it agrees with
+    // the Jacobi symbol. Returns `None` if `p` is not an odd prime.
+    pub fn legendre(&self, p: &BigInt) -> Option<i32> {
+        if p.is_even() || !p.is_probable_prime() {
+            return None;
+        }
+        self.jacobi(p)
+    }
+
+    // A square root of `self` modulo the odd prime `p`, or `None` if there is none, or if `p` is
+    // not an odd prime. Besides the returned root `r`, `p - r` is the other one; we always return
+    // the smaller of the two.
+    //
+    // Writing `p - 1 = q * 2^s` with `q` odd, Tonelli-Shanks takes up to `s^2` multiplications on
+    // top of an exponentiation, while Cipolla always takes about as much as three
+    // exponentiations. So we use Cipolla for primes like `998244353 = 119 * 2^23 + 1`, where `s`
+    // is large.
+    pub fn sqrt_mod(&self, p: &BigInt) -> Option<BigInt> {
+        // For a composite `p`, the search for a non-residue might never end, and the shortcut for
+        // `p = 3 (mod 4)` gives wrong roots.
+        if p.is_even() || !p.is_probable_prime() {
+            return None;
+        }
+        let a = self % p;
+        if a.is_zero() {
+            return Some(a);
+        }
+        if a.jacobi(p)? != 1 {
+            return None;
+        }
+        let p_1 = (p - &BigInt::one()).unwrap();
+        let s = (0..).find(|&i| p_1.bit(i)).unwrap();
+        let r = if s == 1 {
+            // For `p = 3 (mod 4)`, `a^((p+1)/4)` does it.
+            a.mod_pow(&(&(p + &BigInt::one()) >> 2), p)
+        } else if s * s > 8 * p.bits() {
+            cipolla(&a, p)
+        } else {
+            tonelli_shanks(&a, p, s)
+        };
+        let other = (p - &r).unwrap();
+        Some(if other < r { other } else { r })
+    }
+}
+
+// Finds the first `z = 2, 3, ...` for which `f(z)` is a non-residue. Half of all numbers are, so
+// this is quick.
+fn find_non_residue<F: Fn(&BigInt) -> BigInt>(p: &BigInt, f: F) -> BigInt {
+    let mut z = BigInt::new(2);
+    while f(&z).jacobi(p) != Some(-1) {
+        z = &z + &BigInt::one();
+    }
+    z
+}
+
+// Tonelli-Shanks, for a quadratic residue `a` and `p - 1 = q * 2^s`. We start with `r = a^((q+1)/2)`,
+// which satisfies `r^2 = a * t` with `t = a^q`. The order of `t` is a power of two; we repeatedly
+// multiply `r` with a suitable power of a non-residue to lower it, until `t = 1`.
+fn tonelli_shanks(a: &BigInt, p: &BigInt, s: usize) -> BigInt {
+    let one = BigInt::one();
+    let mul = |x: &BigInt, y: &BigInt| &(x * y) % p;
+    let q = &(p - &one).unwrap() >> s;
+    let z = find_non_residue(p, |z| z.clone());
+    let mut m = s;
+    let mut c = z.mod_pow(&q, p);
+    let mut t = a.mod_pow(&q, p);
+    let mut r = a.mod_pow(&(&(&q + &one) >> 1), p);
+    while t != one {
+        // The least `i` with `t^(2^i) = 1`.
+        let mut i = 0;
+        let mut t2 = t.clone();
+        while t2 != one {
+            t2 = mul(&t2, &t2);
+            i += 1;
+        }
+        let mut b = c;
+        for _ in 0..m - i - 1 {
+            b = mul(&b, &b);
+        }
+        m = i;
+        c = mul(&b, &b);
+        t = mul(&t, &c);
+        r = mul(&r, &b);
+    }
+    r
+}
+
+// Cipolla: pick `t` such that `w = t^2 - a` is a non-residue, and compute in the field of
+// `x + y * sqrt(w)`. There, `(t + sqrt(w))^((p+1)/2)` is a square root of `a`, and it lies in the
+// prime field, i.e., `y = 0`.
+fn cipolla(a: &BigInt, p: &BigInt) -> BigInt {
+    let sub = |x: &BigInt, y: &BigInt| &(&(x + p) - y).unwrap() % p;
+    let t = find_non_residue(p, |t| sub(&(&(t * t) % p), a));
+    let w = sub(&(&(&t * &t) % p), a);
+    let mul = |(x1, y1): &(BigInt, BigInt), (x2, y2): &(BigInt, BigInt)| {
+        let x = &(&(x1 * x2) + &(&(&(y1 * y2) % p) * &w)) % p;
+        let y = &(&(x1 * y2) + &(x2 * y1)) % p;
+        (x, y)
+    };
+    let exp = &(p + &BigInt::one()) >> 1;
+    let mut result = (BigInt::one(), BigInt::zero());
+    let base = (t, BigInt::one());
+    for i in (0..exp.bits()).rev() {
+        result = mul(&result, &result);
+        if exp.bit(i) {
+            result = mul(&result, &base);
+        }
+    }
+    result.0
+}
+
+// ## Factorization
+
+// Pollard's rho: iterating `x -> x^2 + c (mod n)` eventually cycles modulo every prime factor
+// `p`, typically after about `sqrt(p)` steps, and usually before it cycles modulo `n`. Floyd's
+// tortoise and hare detect the cycle through `gcd(x - y, n)`. If we are unlucky and find `n`
+// itself, we try another `c`. `n` must be an odd composite.
+fn pollard_rho(n: &BigInt) -> BigInt {
+    let f = |x: &BigInt, c: &BigInt| &(&(x * x) + c) % n;
+    let mut c = BigInt::one();
+    loop {
+        let (mut x, mut y) = (BigInt::new(2), BigInt::new(2));
+        loop {
+            x = f(&x, &c);
+            y = f(&f(&y, &c), &c);
+            let diff = if x > y { &x - &y } else { &y - &x }.unwrap();
+            let d = diff.gcd(n);
+            if d == *n {
+                break;
+            }
+            if d != BigInt::one() {
+                return d;
+            }
+        }
+        c = &c + &BigInt::one();
+    }
+}
+
+// Splits `n` into prime factors, which are appended to `factors`.
+fn split(n: BigInt, factors: &mut Vec<BigInt>) {
+    if n == BigInt::one() {
+        return;
+    }
+    if n.is_probable_prime() {
+        factors.push(n);
+        return;
+    }
+    let d = pollard_rho(&n);
+    let rest = &n / &d;
+    split(d, factors);
+    split(rest, factors);
+}
+
+impl BigInt {
+    // The prime factorization, as pairs of a prime and its exponent, sorted by prime. We divide
+    // out small primes first, and then use Pollard's rho. That handles factors up to around 20
+    // digits; beyond that, the rho method gets slow. Panics for zero, which has no factorization.
+    pub fn factorize(&self) -> Vec<(BigInt, u32)> {
+        assert!(!self.is_zero(), "zero has no prime factorization");
+        let mut factors = vec![];
+        let mut n = self.clone();
+        for p in 2..1000 {
+            // Composite `p` never divide, since we already removed their prime factors.
+            loop {
+                let (q, r) = n.div_rem_u64(p);
+                if r != 0 {
+                    break;
+                }
+                factors.push(BigInt::new(p));
+                n = q;
+            }
+        }
+        split(n, &mut factors);
+        factors.sort_by(|a, b| a.cmp(b));
+        let mut result: Vec<(BigInt, u32)> = vec![];
+        for p in factors {
+            match result.last_mut() {
+                Some(last) if last.0 == p => last.1 += 1,
+                _ => result.push((p, 1)),
+            }
+        }
+        result
+    }
+
+    // Euler's totient: how many numbers in `1..=n` are coprime to `n`. For `n = p1^k1 * ...`, it
+    // is the product of the `p^(k-1) * (p-1)`.
+    pub fn totient(&self) -> BigInt {
+        self.factorize().iter().fold(BigInt::one(), |acc, (p, k)| {
+            &(&acc * &p.pow(k - 1)) * &(p - &BigInt::one()).unwrap()
+        })
+    }
+
+    // The Carmichael function: the smallest `m` such that `a^m = 1 (mod n)` for all `a` coprime to
+    // `n`. It divides the totient. For an odd prime power it is the same as the totient, but powers
+    // of two from 8 on have only half of that, and for products we take the lcm.
+    pub fn carmichael(&self) -> BigInt {
+        let two = BigInt::new(2);
+        self.factorize().iter().fold(BigInt::one(), |acc, (p, k)| {
+            let lambda = if *p == two && *k >= 3 {
+                two.pow(k - 2)
+            } else {
+                &p.pow(k - 1) * &(p - &BigInt::one()).unwrap()
+            };
+            &(&acc / &acc.gcd(&lambda)) * &lambda
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mod_pow_inverse() {
+        let m = BigInt::new(1_000_000_007);
+        assert_eq!(
+            BigInt::new(2).mod_pow(&BigInt::new(1_000_000_006), &m),
+            BigInt::one()
+        );
+        assert_eq!(
+            BigInt::new(3).mod_pow(&BigInt::new(200), &BigInt::new(50)),
+            BigInt::new(1)
+        );
+        assert_eq!(
+            BigInt::new(5).mod_pow(&BigInt::zero(), &BigInt::one()),
+            BigInt::zero()
+        );
+        assert_eq!(
+            BigInt::new(3).mod_inverse(&BigInt::new(11)),
+            Some(BigInt::new(4))
+        );
+        assert_eq!(BigInt::new(4).mod_inverse(&BigInt::new(8)), None);
+    }
+
+    #[test]
+    fn test_is_probable_prime() {
+        let primes: Vec<u64> = (0..100)
+            .filter(|&n| BigInt::new(n).is_probable_prime())
+            .collect();
+        assert_eq!(primes.len(), 25);
+        assert_eq!(primes[..5], [2, 3, 5, 7, 11]);
+        // 2^127 - 1 is a Mersenne prime, while 561 = 3 * 11 * 17 is the smallest Carmichael number.
+        let m127 = (&(BigInt::one() << 127) - &BigInt::one()).unwrap();
+        assert!(m127.is_probable_prime());
+        assert!(!BigInt::new(561).is_probable_prime());
+        assert!(!(&m127 * &BigInt::new(1_000_000_007)).is_probable_prime());
+    }
+
+    #[test]
+    fn test_jacobi_legendre() {
+        // The classic table entries `(1001/9907) = -1` and `(19/45) = 1`.
+        assert_eq!(BigInt::new(1001).jacobi(&BigInt::new(9907)), Some(-1));
+        assert_eq!(BigInt::new(19).jacobi(&BigInt::new(45)), Some(1));
+        assert_eq!(BigInt::new(6).jacobi(&BigInt::new(9)), Some(0));
+        assert_eq!(BigInt::new(3).jacobi(&BigInt::new(8)), None);
+        // Modulo 7, the non-zero squares are 1, 2 and 4.
+        let symbols: Vec<i32> = (0..7)
+            .map(|a| BigInt::new(a).legendre(&BigInt::new(7)).unwrap())
+            .collect();
+        assert_eq!(symbols, [0, 1, 1, -1, 1, -1, -1]);
+        assert_eq!(BigInt::new(2).legendre(&BigInt::new(9)), None);
+    }
+
+    #[test]
+    fn test_sqrt_mod() {
+        // Both algorithms, against brute force, for all small primes with `s > 1`.
+        for p in (5..200u64).filter(|&p| BigInt::new(p).is_probable_prime() && p % 4 == 1) {
+            let big_p = BigInt::new(p);
+            let s = (p - 1).trailing_zeros() as usize;
+            for a in 1..p {
+                let has_root = (1..p).any(|x| x * x % p == a);
+                let a = BigInt::new(a);
+                assert_eq!(a.sqrt_mod(&big_p).is_some(), has_root);
+                if has_root {
+                    for r in [tonelli_shanks(&a, &big_p, s), cipolla(&a, &big_p)].iter() {
+                        assert_eq!(&(r * r) % &big_p, a);
+                    }
+                }
+            }
+        }
+        // Large `s` goes to Cipolla; `p = 3 (mod 4)` takes the shortcut.
+        let p = BigInt::new(998_244_353);
+        let r = BigInt::new(5).pow(2).sqrt_mod(&p).unwrap();
+        assert_eq!(r, BigInt::new(5));
+        let p = BigInt::new(1_000_000_007);
+        let r = BigInt::new(11).sqrt_mod(&p).unwrap();
+        assert_eq!(&(&r * &r) % &p, BigInt::new(11));
+        assert_eq!(BigInt::new(5).sqrt_mod(&p), None);
+        // Not odd primes: a square, a composite `p = 3 (mod 4)`, two and zero.
+        for p in [9, 15, 2, 0] {
+            assert_eq!(BigInt::new(4).sqrt_mod(&BigInt::new(p)), None);
+        }
+    }
+
+    #[test]
+    fn test_totient_carmichael() {
+        let n: BigInt = "600851475143".parse().unwrap();
+        let factors: Vec<String> = n
+            .factorize()
+            .iter()
+            .map(|(p, k)| format!("{}^{}", p, k))
+            .collect();
+        assert_eq!(factors, ["71^1", "839^1", "1471^1", "6857^1"]);
+        // A product of two 10-digit primes needs Pollard's rho.
+        let (p, q) = (BigInt::new(1_000_000_007), BigInt::new(998_244_353));
+        let pq = &p * &q;
+        assert_eq!(pq.factorize(), vec![(q.clone(), 1), (p.clone(), 1)]);
+
+        assert_eq!(BigInt::new(36).totient(), BigInt::new(12));
+        assert_eq!(BigInt::one().totient(), BigInt::one());
+        assert_eq!(BigInt::new(561).carmichael(), BigInt::new(80));
+        assert_eq!(BigInt::new(32).carmichael(), BigInt::new(8));
+        assert_eq!(BigInt::new(15).carmichael(), BigInt::new(4));
+    }
+}
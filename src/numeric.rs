@@ -0,0 +1,282 @@
+// Numeric Traits
+// ==============
+
+// Parts 00 to 04 write `vec_min` and `vec_sum` once per number type, and part 02 and part 07 each
+// come up with their own `Minimum` trait, one for `Copy` types and one for borrowed `BigInt`s.
+// Here we collect what those functions actually need from a number into a few small traits, and
+// implement them for the primitive types as well as for our big integers. Generic code can then be
+// written once, against `Number`.
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::cmp::Ordering;
+
+// ## The traits
+
+// The neutral element of addition. `vec_sum` starts from it, and returns it for an empty vector.
+pub trait Zero: Sized {
+    fn zero() -> Self;
+    fn is_zero(&self) -> bool;
+}
+
+// The neutral element of multiplication.
+pub trait One: Sized {
+    fn one() -> Self;
+}
+
+// Arithmetic that reports overflow with `None`, instead of panicking (in debug builds) or wrapping
+// around (in release builds). The operands are borrowed, so that `BigInt`s are not copied.
+pub trait CheckedAdd: Sized {
+    fn checked_add(&self, other: &Self) -> Option<Self>;
+}
+
+pub trait CheckedSub: Sized {
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+}
+
+pub trait CheckedMul: Sized {
+    fn checked_mul(&self, other: &Self) -> Option<Self>;
+}
+
+// Everything together. For ordering, we only ask for `PartialOrd`: floats are not totally ordered,
+// since NaN compares neither smaller nor larger than anything, and `BigInt` brings its own `cmp`.
+pub trait Number: Clone + PartialOrd + Zero + One + CheckedAdd + CheckedSub + CheckedMul {
+    // Compares the two numbers, treating unordered pairs (involving a NaN) as equal.
+    fn num_cmp(&self, other: &Self) -> Ordering {
+        self.partial_cmp(other).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl<T: Clone + PartialOrd + Zero + One + CheckedAdd + CheckedSub + CheckedMul> Number for T {}
+
+// ## Primitive types
+
+// The integer types have all of this built in already, we just have to forward to it.
+macro_rules! impl_integer {
+    ($($t:ty),*) => {$(
+        impl Zero for $t {
+            fn zero() -> Self {
+                0
+            }
+            fn is_zero(&self) -> bool {
+                *self == 0
+            }
+        }
+
+        impl One for $t {
+            fn one() -> Self {
+                1
+            }
+        }
+
+        impl CheckedAdd for $t {
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_add(*self, *other)
+            }
+        }
+
+        impl CheckedSub for $t {
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_sub(*self, *other)
+            }
+        }
+
+        impl CheckedMul for $t {
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                <$t>::checked_mul(*self, *other)
+            }
+        }
+    )*};
+}
+
+impl_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+// Floats do not overflow, they become infinite. We count that as overflow, but only if both
+// operands were finite: adding to an infinity that is already there is fine.
+macro_rules! impl_float {
+    ($($t:ty),*) => {$(
+        impl Zero for $t {
+            fn zero() -> Self {
+                0.0
+            }
+            fn is_zero(&self) -> bool {
+                *self == 0.0
+            }
+        }
+
+        impl One for $t {
+            fn one() -> Self {
+                1.0
+            }
+        }
+
+        impl CheckedAdd for $t {
+            fn checked_add(&self, other: &Self) -> Option<Self> {
+                check_finite(*self, *other, *self + *other)
+            }
+        }
+
+        impl CheckedSub for $t {
+            fn checked_sub(&self, other: &Self) -> Option<Self> {
+                check_finite(*self, *other, *self - *other)
+            }
+        }
+
+        impl CheckedMul for $t {
+            fn checked_mul(&self, other: &Self) -> Option<Self> {
+                check_finite(*self, *other, *self * *other)
+            }
+        }
+    )*};
+}
+
+impl_float!(f32, f64);
+
+fn check_finite<T: Into<f64> + Copy>(a: T, b: T, result: T) -> Option<T> {
+    let finite = |x: T| x.into().is_finite();
+    if finite(a) && finite(b) && !finite(result) {
+        None
+    } else {
+        Some(result)
+    }
+}
+
+// ## Big integers
+
+// `BigInt` never overflows, but it cannot go below zero, which is where `checked_sub` comes in.
+impl Zero for BigInt {
+    fn zero() -> Self {
+        BigInt::zero()
+    }
+    fn is_zero(&self) -> bool {
+        BigInt::is_zero(self)
+    }
+}
+
+impl One for BigInt {
+    fn one() -> Self {
+        BigInt::one()
+    }
+}
+
+impl CheckedAdd for BigInt {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+}
+
+impl CheckedSub for BigInt {
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self - other
+    }
+}
+
+impl CheckedMul for BigInt {
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+impl Zero for SignedBigInt {
+    fn zero() -> Self {
+        SignedBigInt::zero()
+    }
+    fn is_zero(&self) -> bool {
+        SignedBigInt::is_zero(self)
+    }
+}
+
+impl One for SignedBigInt {
+    fn one() -> Self {
+        SignedBigInt::one()
+    }
+}
+
+impl CheckedAdd for SignedBigInt {
+    fn checked_add(&self, other: &Self) -> Option<Self> {
+        Some(self + other)
+    }
+}
+
+impl CheckedSub for SignedBigInt {
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+}
+
+impl CheckedMul for SignedBigInt {
+    fn checked_mul(&self, other: &Self) -> Option<Self> {
+        Some(self * other)
+    }
+}
+
+// ## Generic helpers
+
+// The minimum, borrowed as in part 07, so that no `BigInt` gets copied. NaNs never win a comparison,
+// so they are only returned if they come first.
+pub fn vec_min<T: Number>(v: &[T]) -> Option<&T> {
+    let mut min: Option<&T> = None;
+    for e in v {
+        min = Some(match min {
+            Some(m) if m.num_cmp(e) != Ordering::Greater => m,
+            _ => e,
+        });
+    }
+    min
+}
+
+pub fn vec_max<T: Number>(v: &[T]) -> Option<&T> {
+    let mut max: Option<&T> = None;
+    for e in v {
+        max = Some(match max {
+            Some(m) if m.num_cmp(e) != Ordering::Less => m,
+            _ => e,
+        });
+    }
+    max
+}
+
+// The sum, or `None` if it overflows. The sum of nothing is zero.
+pub fn vec_sum<T: Number>(v: &[T]) -> Option<T> {
+    v.iter().try_fold(T::zero(), |acc, e| acc.checked_add(e))
+}
+
+pub fn vec_product<T: Number>(v: &[T]) -> Option<T> {
+    v.iter().try_fold(T::one(), |acc, e| acc.checked_mul(e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitives() {
+        assert_eq!(vec_min(&[3, -7, 12]), Some(&-7));
+        assert_eq!(vec_max(&[3u8, 7, 1]), Some(&7));
+        assert_eq!(vec_min::<i32>(&[]), None);
+        assert_eq!(vec_sum(&[1, 2, 3]), Some(6));
+        assert_eq!(vec_sum::<i64>(&[]), Some(0));
+        assert_eq!(vec_sum(&[i32::MAX, 1]), None);
+        assert_eq!(vec_product(&[1u8, 16, 16]), None);
+        assert_eq!(vec_min(&[2.5, -1.0, 0.0]), Some(&-1.0));
+        assert_eq!(vec_sum(&[1.5f32, 2.25]), Some(3.75));
+        assert_eq!(vec_sum(&[f64::MAX, f64::MAX]), None);
+        assert_eq!(vec_sum(&[f64::INFINITY, 1.0]), Some(f64::INFINITY));
+    }
+
+    #[test]
+    fn test_big_integers() {
+        let v = vec![BigInt::new(5), BigInt::from_vec(vec![0, 1]), BigInt::new(3)];
+        assert_eq!(vec_min(&v), Some(&v[2]));
+        assert_eq!(vec_max(&v), Some(&v[1]));
+        assert_eq!(vec_sum(&v), Some(BigInt::from_vec(vec![8, 1])));
+        assert_eq!(vec_product(&v), Some(BigInt::from_vec(vec![0, 15])));
+        assert_eq!(CheckedSub::checked_sub(&v[2], &v[0]), None);
+        // Where `u64` overflows, `BigInt` just keeps going.
+        assert_eq!(vec_sum(&[u64::MAX, 1]), None);
+        let big = [BigInt::new(u64::MAX), BigInt::one()];
+        assert_eq!(vec_sum(&big), Some(BigInt::from_vec(vec![0, 1])));
+        let signed: Vec<SignedBigInt> = vec![SignedBigInt::from(-4), SignedBigInt::from(9)];
+        assert_eq!(vec_sum(&signed), Some(SignedBigInt::from(5)));
+        assert_eq!(vec_min(&signed), Some(&signed[0]));
+    }
+}
@@ -109,6 +109,33 @@ fn vec_sum(v: Vec<i32>) -> NumberOrNothing {
     sum
 }
 
+// `s + e` panics when the sum does not fit in an `i32` (in a debug build), or wraps around (in a
+// release build). `checked_add` tells us instead. The module `summation` has more: a version for
+// any number type, and one that switches to big integers.
+use summation::Overflow;
+fn vec_sum_checked(v: Vec<i32>) -> Result<NumberOrNothing, Overflow> {
+    let mut sum = Nothing;
+    for (index, e) in v.into_iter().enumerate() {
+        sum = Number(match sum {
+            Number(s) => s.checked_add(e).ok_or(Overflow { index })?,
+            Nothing => e,
+        });
+    }
+    Ok(sum)
+}
+
+#[test]
+fn test_vec_sum_checked() {
+    assert_eq!(
+        number_or_default(vec_sum_checked(vec![1, 2]).unwrap(), 0),
+        3
+    );
+    assert_eq!(
+        vec_sum_checked(vec![i32::MAX, 1, -1]).err(),
+        Some(Overflow { index: 1 })
+    );
+}
+
 // **Exercise 01.2**: Write a function `vec_print` that takes a vector and prints all its elements.
 
 fn vec_print(v: Vec<i32>) {
@@ -3,8 +3,10 @@
 
 // ## Generic datatypes
 // #[derive(Clone)]
+#[derive(Debug, Default, PartialEq, Eq)]
 pub enum SomethingOrNothing<T> {
     Something(T),
+    #[default]
     Nothing,
 }
 use std::fmt::Display;
@@ -85,6 +87,179 @@ impl<T: Display> Display for SomethingOrNothing<T> {
     }
 }
 
+// ## The `Option` API
+
+// `SomethingOrNothing<T>` is the same type as `Option<T>` from part 04, just under another name. So
+// that code can use either one without converting back and forth all the time, we give it the same
+// methods, and conversions in both directions. Most of them are a single `match`, and for the rest
+// we go through `Option`.
+impl<T> SomethingOrNothing<T> {
+    pub fn is_something(&self) -> bool {
+        match self {
+            Something(_) => true,
+            Nothing => false,
+        }
+    }
+
+    pub fn is_nothing(&self) -> bool {
+        !self.is_something()
+    }
+
+    pub fn as_ref(&self) -> SomethingOrNothing<&T> {
+        match self {
+            Something(t) => Something(t),
+            Nothing => Nothing,
+        }
+    }
+
+    pub fn as_mut(&mut self) -> SomethingOrNothing<&mut T> {
+        match self {
+            Something(t) => Something(t),
+            Nothing => Nothing,
+        }
+    }
+
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> SomethingOrNothing<U> {
+        match self {
+            Something(t) => Something(f(t)),
+            Nothing => Nothing,
+        }
+    }
+
+    pub fn and_then<U, F: FnOnce(T) -> SomethingOrNothing<U>>(self, f: F) -> SomethingOrNothing<U> {
+        match self {
+            Something(t) => f(t),
+            Nothing => Nothing,
+        }
+    }
+
+    pub fn or(self, other: Self) -> Self {
+        match self {
+            Something(t) => Something(t),
+            Nothing => other,
+        }
+    }
+
+    pub fn or_else<F: FnOnce() -> Self>(self, f: F) -> Self {
+        match self {
+            Something(t) => Something(t),
+            Nothing => f(),
+        }
+    }
+
+    pub fn unwrap_or(self, default: T) -> T {
+        match self {
+            Something(t) => t,
+            Nothing => default,
+        }
+    }
+
+    pub fn unwrap_or_else<F: FnOnce() -> T>(self, f: F) -> T {
+        match self {
+            Something(t) => t,
+            Nothing => f(),
+        }
+    }
+
+    pub fn filter<P: FnOnce(&T) -> bool>(self, predicate: P) -> Self {
+        match self {
+            Something(t) if predicate(&t) => Something(t),
+            _ => Nothing,
+        }
+    }
+
+    pub fn zip<U>(self, other: SomethingOrNothing<U>) -> SomethingOrNothing<(T, U)> {
+        match (self, other) {
+            (Something(t), Something(u)) => Something((t, u)),
+            _ => Nothing,
+        }
+    }
+
+    // Moves the value out, and leaves `Nothing` in its place.
+    pub fn take(&mut self) -> Self {
+        std::mem::replace(self, Nothing)
+    }
+
+    pub fn iter(&self) -> std::option::IntoIter<&T> {
+        self.as_ref().to_option().into_iter()
+    }
+}
+
+impl<T> From<Option<T>> for SomethingOrNothing<T> {
+    fn from(o: Option<T>) -> Self {
+        SomethingOrNothing::new(o)
+    }
+}
+
+impl<T> From<SomethingOrNothing<T>> for Option<T> {
+    fn from(s: SomethingOrNothing<T>) -> Self {
+        s.to_option()
+    }
+}
+
+impl<T> IntoIterator for SomethingOrNothing<T> {
+    type Item = T;
+    type IntoIter = std::option::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_option().into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SomethingOrNothing<T> {
+    type Item = &'a T;
+    type IntoIter = std::option::IntoIter<&'a T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+// As for `Option`, collecting an iterator of `SomethingOrNothing`s gives `Nothing` as soon as one
+// element is `Nothing`, and otherwise all the values, collected into `V` (e.g., a `Vec`).
+impl<T, V: std::iter::FromIterator<T>> std::iter::FromIterator<SomethingOrNothing<T>>
+    for SomethingOrNothing<V>
+{
+    fn from_iter<I: IntoIterator<Item = SomethingOrNothing<T>>>(iter: I) -> Self {
+        iter.into_iter()
+            .map(SomethingOrNothing::to_option)
+            .collect::<Option<V>>()
+            .into()
+    }
+}
+
+#[test]
+fn test_option_api() {
+    let five = Something(5);
+    assert_eq!(five.map(|n| n * 2), Something(10));
+    assert_eq!(
+        five.and_then(|n| if n > 3 { Something(n) } else { Nothing }),
+        five
+    );
+    assert_eq!(five.filter(|&n| n > 5), Nothing);
+    assert_eq!(Nothing.or(five), five);
+    assert_eq!(Nothing.or_else(|| Something(1)), Something(1));
+    assert_eq!(SomethingOrNothing::<i32>::default().unwrap_or(7), 7);
+    assert_eq!(five.zip(Something('x')), Something((5, 'x')));
+    assert_eq!(five.zip(Nothing::<char>), Nothing);
+    let mut s = Something(String::from("a"));
+    s.as_mut().map(|t| t.push('b'));
+    assert_eq!(s.as_ref().map(|t| t.len()), Something(2));
+    assert_eq!(s.take(), Something(String::from("ab")));
+    assert!(s.is_nothing());
+}
+
+#[test]
+fn test_option_conversions() {
+    let o: Option<i32> = Something(3).into();
+    assert_eq!(o, Some(3));
+    assert_eq!(SomethingOrNothing::from(None::<i32>), Nothing);
+    assert_eq!(Something(4).iter().collect::<Vec<_>>(), vec![&4]);
+    assert_eq!(Nothing::<i32>.into_iter().count(), 0);
+    let all: SomethingOrNothing<Vec<i32>> = vec![Something(1), Something(2)].into_iter().collect();
+    assert_eq!(all, Something(vec![1, 2]));
+    let some: SomethingOrNothing<Vec<i32>> = vec![Something(1), Nothing].into_iter().collect();
+    assert_eq!(some, Nothing);
+}
+
 // Now we are ready to run our new code. Remember to change `main.rs` appropriately.
 fn read_vec() -> Vec<i32> {
     vec![18, 5, 7, 3, 9, 27]
@@ -106,12 +281,18 @@ pub fn main() {
 
 type FloatOrNothing = SomethingOrNothing<f32>;
 
+use float::{min_with, NanPolicy};
+
+// Comparing with `<` would make the result depend on where a NaN is in the vector, so we let NaNs
+// win instead. See the module `float` for other choices.
 impl Minimum for f32 {
     fn min(self, b: Self) -> Self {
-        if self < b {
-            self
-        } else {
-            b
-        }
+        min_with(self, b, NanPolicy::Propagate)
+    }
+}
+
+impl Minimum for f64 {
+    fn min(self, b: Self) -> Self {
+        min_with(self, b, NanPolicy::Propagate)
     }
 }
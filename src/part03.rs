@@ -7,46 +7,103 @@
 use std::io::prelude::*;
 use std::{io, str::FromStr};
 
-fn read_vec<T: FromStr>() -> Vec<T> {
-    let mut vec: Vec<T> = Vec::<T>::new();
+// Reading works with any `BufRead`: standard input, a file wrapped in an `io::BufReader`, or, in a
+// test, a string as bytes. What to do with things that are not numbers is up to the caller, with
+// `policy`. Numbers may be separated by spaces, commas or newlines, and `#` starts a comment.
+pub fn read_vec<T: FromStr, R: BufRead>(
+    input: R,
+    policy: BadLines,
+) -> Result<Numbers<T, T::Err>, ReadError<T::Err>> {
+    // The loop over the lines lives in `input::read_with`. Rust's type for (dynamic, growable)
+    // strings is `String`. However, the lines we get from `input.lines()` are not yet of that type:
+    // They have type `io::Result<String>`, since reading can fail. Rather than `unwrap` that, it
+    // turns a failure into a `ReadError::Io` saying on which line it happened, and hands it back
+    // with `?`. Then it shadows `line` with the `String`, choosing the same name to ensure that it
+    // will never, accidentally, access the "old" `line` again.
+    //
+    // Then it makes the `String` into a `T`. We used to ignore the particular error with a `_`, but
+    // now it is kept, together with the line number and the text, in a `LineError`.
+    input::read_with(input, &Format::values(), policy)
+}
+
+// The `main` functions below read from standard input, and report the bad numbers as they go. If
+// reading itself fails, we report that and return `None`, so that they stop rather than work with
+// only part of the numbers.
+fn read_stdin<T: FromStr>() -> Option<Vec<T>>
+where
+    T::Err: Display,
+{
     // The central handle to the standard input is made available by the function `io::stdin`.
     let stdin = io::stdin();
-    println!("Enter a list of numbers, one per line. End with Ctrl-D (Linux) or Ctrl-Z (Windows).");
-    for line in stdin.lock().lines() {
-        // Rust's type for (dynamic, growable) strings is `String`. However, our variable `line`
-        // here is not yet of that type: It has type `io::Result<String>`.
-
-        // I chose the same name (`line`) for the new variable to ensure that I will never,
-        // accidentally, access the "old" `line` again.
-        let line = line.unwrap();
-        // Now that we have our `String`, we want to make it an `i32`.
-
-        match line.trim().parse::<T>() {
-            Ok(num) => {
-                vec.push(num);
-            }
-            // We don't care about the particular error, so we ignore it with a `_`.
-            Err(_) => {
-                println!("Input numbers");
-            }
+    println!(
+        "Enter a list of numbers, separated by spaces, commas or newlines; `#` starts a comment."
+    );
+    println!("End with Ctrl-D (Linux) or Ctrl-Z (Windows).");
+    let numbers = match read_vec(stdin.lock(), BadLines::Collect) {
+        Ok(numbers) => numbers,
+        Err(e) => {
+            println!("{}", e);
+            return None;
         }
+    };
+    for e in &numbers.errors {
+        println!("Input numbers ({})", e);
     }
-
-    vec
+    Some(numbers.values)
 }
 
+use input::{self, BadLines, Format, Numbers, ReadError};
+use std::fmt::Display;
+
 // For the rest of the code, we just re-use part 02 by importing it with `use`.
 use part02::{vec_min, Nothing, Something, SomethingOrNothing};
 
 // If you update your `main.rs` to use part 03, `cargo run` should now ask you for some numbers,
 // and tell you the minimum. Neat, isn't it?
 pub fn main() {
-    let vec = read_vec::<i32>();
+    let Some(vec) = read_stdin::<i32>() else {
+        return;
+    };
     let min = vec_min(vec);
     // min.print2();
     println!("{min}");
 }
 
+// For floats, `parse` accepts `NaN` (and `inf`) just as well as ordinary numbers. `vec_min` from
+// part 02 would then just report `NaN`, so here we skip them, and say how many we skipped. For the
+// sum, we use compensated summation, so that the rounding errors do not pile up.
+use float::{self, NanPolicy};
+use random::Rng;
+use statistics;
+use streaming::{aggregate, Aggregator};
+use summation::neumaier_sum;
+pub fn main_f64() {
+    let Some(vec) = read_stdin::<f64>() else {
+        return;
+    };
+    let min = float::vec_min(&vec, NanPolicy::Ignore);
+    println!("{min}");
+    println!("The sum is: {}", neumaier_sum(vec.iter().copied()));
+}
+
+// Or everything at once: the module `statistics` prints a summary and a histogram.
+pub fn main_statistics() {
+    let Some(vec) = read_stdin::<f64>() else {
+        return;
+    };
+    statistics::print_report(&vec, 10);
+}
+
+// The functions above wait for the end of the input. This one prints a summary every 1000 lines,
+// and never holds more than a few numbers in memory, so it also works on endless input.
+pub fn main_streaming() {
+    let mut aggregator = Aggregator::new(&[0.5, 0.9, 0.99], 10, Rng::from_entropy());
+    let stdin = io::stdin();
+    if let Err(e) = aggregate(stdin.lock(), &mut aggregator, 1000, &mut io::stdout()) {
+        println!("{}", e);
+    }
+}
+
 // **Exercise 03.1**: The goal is to write a generic version of `SomethingOrNothing::print`.
 // To this end, define a trait `Print` that provides (simple) generic printing, and implement
 // that trait for `i32`. Then define `SomethingOrNothing::print2` to use that trait, and change
@@ -91,3 +148,16 @@ impl Print for f32 {
         print!("The f32 is: {}", self);
     }
 }
+
+#[test]
+fn test_read_vec() {
+    let input = "3, -7 # the first two\nseven\n0x10\n";
+    let numbers = read_vec::<i32, _>(input.as_bytes(), BadLines::Collect).unwrap();
+    assert_eq!(numbers.values, vec![3, -7, 16]);
+    assert_eq!(numbers.errors[0].line, 2);
+    let failed = read_vec::<i32, _>(input.as_bytes(), BadLines::Fail);
+    assert_eq!(
+        failed.unwrap_err().to_string(),
+        "line 2: \"seven\" is not a number: invalid digit found in string"
+    );
+}
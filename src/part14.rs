@@ -4,6 +4,17 @@
 // ## Slices
 
 pub fn sort<T: PartialOrd>(data: &mut [T]) {
+    // Elements that do not compare, like NaN, count as equal here. That keeps the loop below from
+    // going wrong, but where they end up is arbitrary. Use `sort_by` to decide.
+    sort_by(data, &mut |a: &T, b: &T| {
+        a.partial_cmp(b).unwrap_or(Ordering::Equal)
+    });
+}
+
+use std::cmp::Ordering;
+
+// The same Quicksort, with the order given by a comparison function.
+pub fn sort_by<T, F: FnMut(&T, &T) -> Ordering>(data: &mut [T], cmp: &mut F) {
     if data.len() < 2 {
         return;
     }
@@ -21,7 +32,7 @@ pub fn sort<T: PartialOrd>(data: &mut [T]) {
         if lpos == rpos {
             break;
         }
-        if data[lpos] <= data[0] {
+        if cmp(&data[lpos], &data[0]) != Ordering::Greater {
             lpos += 1;
         } else {
             loop {
@@ -29,7 +40,7 @@ pub fn sort<T: PartialOrd>(data: &mut [T]) {
                 if lpos == rpos {
                     break;
                 }
-                if data[rpos] < data[0] {
+                if cmp(&data[rpos], &data[0]) == Ordering::Less {
                     data.swap(lpos, rpos);
                     lpos += 1;
                     break;
@@ -44,8 +55,8 @@ pub fn sort<T: PartialOrd>(data: &mut [T]) {
     // Finally, we split our slice to sort the two halves. The nice part about slices is that
     // splitting them is cheap:
     let (part1, part2) = data.split_at_mut(lpos);
-    sort(&mut part1[..lpos - 1]);
-    sort(part2);
+    sort_by(&mut part1[..lpos - 1], cmp);
+    sort_by(part2, cmp);
 }
 
 #[test]
@@ -71,7 +82,29 @@ fn sort_nums(data: &mut Vec<i32>) {
 // ## Arrays
 fn sort_array() {
     let mut array_of_data: [f64; 5] = [1.0, 3.4, 12.7, -9.12, 0.1];
-    sort(&mut array_of_data);
+    sort_floats(&mut array_of_data, NanPolicy::Ignore);
+}
+
+// Floats need a decision about NaN. With `Ignore` and `Propagate`, all NaNs end up at the back.
+use float::{cmp_with, Float, NanPolicy};
+pub fn sort_floats<T: Float>(data: &mut [T], policy: NanPolicy) {
+    sort_by(data, &mut |a: &T, b: &T| cmp_with(a, b, policy));
+}
+
+#[test]
+fn test_sort_floats() {
+    let mut data = [1.0, f64::NAN, 12.7, -9.12, f64::NAN, 0.1];
+    sort_floats(&mut data, NanPolicy::Ignore);
+    assert_eq!(&data[..4], &[-9.12, 0.1, 1.0, 12.7]);
+    assert!(data[4].is_nan() && data[5].is_nan());
+    let mut data = [f32::INFINITY, -f32::NAN, -0.0, 0.0, -0.0];
+    sort_floats(&mut data, NanPolicy::TotalOrder);
+    let bits: Vec<u32> = data.iter().map(|x| x.to_bits()).collect();
+    let expected = [-f32::NAN, -0.0, -0.0, 0.0, f32::INFINITY];
+    assert_eq!(
+        bits,
+        expected.iter().map(|x| x.to_bits()).collect::<Vec<_>>()
+    );
 }
 
 // ## External Dependencies
@@ -0,0 +1,318 @@
+// Polynomials over Big Integers
+// =============================
+
+// A dense polynomial is just its list of coefficients. Just like the digits of a `BigInt`, we store
+// them lowest degree first and without trailing zeros, so `3x^2 + 2x + 1` is `[1, 2, 3]` and the
+// zero polynomial is the empty vector.
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::{fmt, ops};
+
+// The operations a coefficient type has to provide. Subtraction is checked, because `BigInt` cannot
+// go negative (see part 08); for `SignedBigInt` it always succeeds.
+pub trait Coefficient: Clone + PartialEq + fmt::Display {
+    fn zero() -> Self;
+    fn from_u64(n: u64) -> Self;
+    fn is_zero(&self) -> bool;
+    fn is_negative(&self) -> bool;
+    fn abs(&self) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn checked_sub(&self, other: &Self) -> Option<Self>;
+    fn mul(&self, other: &Self) -> Self;
+}
+
+impl Coefficient for BigInt {
+    fn zero() -> Self {
+        BigInt::zero()
+    }
+    fn from_u64(n: u64) -> Self {
+        BigInt::new(n)
+    }
+    fn is_zero(&self) -> bool {
+        BigInt::is_zero(self)
+    }
+    fn is_negative(&self) -> bool {
+        false
+    }
+    fn abs(&self) -> Self {
+        self.clone()
+    }
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self - other
+    }
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+impl Coefficient for SignedBigInt {
+    fn zero() -> Self {
+        SignedBigInt::zero()
+    }
+    fn from_u64(n: u64) -> Self {
+        SignedBigInt::from(BigInt::new(n))
+    }
+    fn is_zero(&self) -> bool {
+        SignedBigInt::is_zero(self)
+    }
+    fn is_negative(&self) -> bool {
+        SignedBigInt::is_negative(self)
+    }
+    fn abs(&self) -> Self {
+        SignedBigInt::abs(self)
+    }
+    fn add(&self, other: &Self) -> Self {
+        self + other
+    }
+    fn checked_sub(&self, other: &Self) -> Option<Self> {
+        Some(self - other)
+    }
+    fn mul(&self, other: &Self) -> Self {
+        self * other
+    }
+}
+
+#[derive(Clone, PartialEq)]
+pub struct Polynomial<T> {
+    coeffs: Vec<T>, // lowest degree first, no trailing zeros
+}
+
+impl<T: Coefficient> Polynomial<T> {
+    // Like `BigInt::from_vec`, this removes trailing zeros to establish the invariant.
+    pub fn new(mut coeffs: Vec<T>) -> Self {
+        while coeffs.last().map(|c| c.is_zero()).unwrap_or(false) {
+            coeffs.pop();
+        }
+        Polynomial { coeffs }
+    }
+
+    pub fn zero() -> Self {
+        Polynomial { coeffs: vec![] }
+    }
+
+    // The polynomial `c * x^degree`.
+    pub fn monomial(c: T, degree: usize) -> Self {
+        let mut coeffs = vec![T::zero(); degree];
+        coeffs.push(c);
+        Polynomial::new(coeffs)
+    }
+
+    pub fn coeffs(&self) -> &[T] {
+        &self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    // The zero polynomial has no degree.
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn leading_coeff(&self) -> Option<&T> {
+        self.coeffs.last()
+    }
+
+    // Evaluates the polynomial at `x` with Horner's rule: `a0 + x(a1 + x(a2 + ...))`. That needs
+    // only one multiplication per coefficient, and no powers of `x`.
+    pub fn eval(&self, x: &T) -> T {
+        let mut acc = T::zero();
+        for c in self.coeffs.iter().rev() {
+            acc = acc.mul(x).add(c);
+        }
+        acc
+    }
+
+    pub fn derivative(&self) -> Self {
+        let coeffs = self
+            .coeffs
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(i, c)| c.mul(&T::from_u64(i as u64)))
+            .collect();
+        Polynomial::new(coeffs)
+    }
+
+    // Subtraction is checked like that of the coefficients.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let len = self.coeffs.len().max(other.coeffs.len());
+        let zero = T::zero();
+        let mut coeffs = Vec::with_capacity(len);
+        for i in 0..len {
+            let a = self.coeffs.get(i).unwrap_or(&zero);
+            let b = other.coeffs.get(i).unwrap_or(&zero);
+            coeffs.push(a.checked_sub(b)?);
+        }
+        Some(Polynomial::new(coeffs))
+    }
+
+    // Divides by a *monic* polynomial, i.e., one with leading coefficient 1. Then long division
+    // never has to divide coefficients, so it works over the integers. Returns `None` if the
+    // divisor is not monic, or if a coefficient would become negative with `BigInt` coefficients.
+    pub fn div_rem(&self, divisor: &Self) -> Option<(Self, Self)> {
+        let d = divisor.degree()?;
+        if *divisor.leading_coeff()? != T::from_u64(1) {
+            return None;
+        }
+        let mut rem = self.coeffs.clone();
+        if rem.len() <= d {
+            return Some((Polynomial::zero(), self.clone()));
+        }
+        let mut quot = vec![T::zero(); rem.len() - d];
+        // Eliminate the leading term of the remainder, from the top down.
+        for i in (0..quot.len()).rev() {
+            let q = rem[i + d].clone();
+            for (j, c) in divisor.coeffs.iter().enumerate() {
+                rem[i + j] = rem[i + j].checked_sub(&q.mul(c))?;
+            }
+            quot[i] = q;
+        }
+        rem.truncate(d);
+        Some((Polynomial::new(quot), Polynomial::new(rem)))
+    }
+}
+
+// ## Arithmetic
+
+impl<T: Coefficient> ops::Add<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+    fn add(self, rhs: &Polynomial<T>) -> Polynomial<T> {
+        let (long, short) = if self.coeffs.len() >= rhs.coeffs.len() {
+            (self, rhs)
+        } else {
+            (rhs, self)
+        };
+        let mut coeffs = long.coeffs.clone();
+        for (i, c) in short.coeffs.iter().enumerate() {
+            coeffs[i] = coeffs[i].add(c);
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+impl<T: Coefficient> ops::Mul<&Polynomial<T>> for &Polynomial<T> {
+    type Output = Polynomial<T>;
+    fn mul(self, rhs: &Polynomial<T>) -> Polynomial<T> {
+        if self.is_zero() || rhs.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut coeffs = vec![T::zero(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = coeffs[i + j].add(&a.mul(b));
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+// With signed coefficients, subtraction cannot fail, so we provide the operator.
+impl ops::Sub<&Polynomial<SignedBigInt>> for &Polynomial<SignedBigInt> {
+    type Output = Polynomial<SignedBigInt>;
+    fn sub(self, rhs: &Polynomial<SignedBigInt>) -> Polynomial<SignedBigInt> {
+        self.checked_sub(rhs).unwrap()
+    }
+}
+
+impl ops::Neg for &Polynomial<SignedBigInt> {
+    type Output = Polynomial<SignedBigInt>;
+    fn neg(self) -> Polynomial<SignedBigInt> {
+        Polynomial::new(self.coeffs.iter().map(|c| -c).collect())
+    }
+}
+
+// ## Formatting
+
+// We print the usual notation, highest degree first: `3x^2 - x + 1`. Coefficients of 1 are left
+// out, except for the constant term.
+impl<T: Coefficient> fmt::Display for Polynomial<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let one = T::from_u64(1);
+        let mut first = true;
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+            match (first, c.is_negative()) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => {}
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            first = false;
+            let abs = c.abs();
+            if abs != one || i == 0 {
+                write!(f, "{}", abs)?;
+            }
+            match i {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", i)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Coefficient> fmt::Debug for Polynomial<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poly(coeffs: &[i64]) -> Polynomial<SignedBigInt> {
+        Polynomial::new(coeffs.iter().map(|&c| SignedBigInt::from(c)).collect())
+    }
+
+    fn upoly(coeffs: &[u64]) -> Polynomial<BigInt> {
+        Polynomial::new(coeffs.iter().map(|&c| BigInt::new(c)).collect())
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(upoly(&[1, 2, 3]).to_string(), "3x^2 + 2x + 1");
+        assert_eq!(poly(&[-1, 0, -1, 1]).to_string(), "x^3 - x^2 - 1");
+        assert_eq!(poly(&[0, -1]).to_string(), "-x");
+        assert_eq!(poly(&[0, 0, 0]).to_string(), "0");
+        assert_eq!(poly(&[0, 0, 0]).degree(), None);
+    }
+
+    #[test]
+    fn test_arith() {
+        let p = poly(&[1, 1]);
+        let q = poly(&[-1, 1]);
+        assert_eq!(&p * &q, poly(&[-1, 0, 1]));
+        assert_eq!(&p - &p, Polynomial::zero());
+        assert_eq!(&p + &(-&q), poly(&[2]));
+        assert_eq!(upoly(&[1, 2]).checked_sub(&upoly(&[2])), None);
+        assert_eq!(upoly(&[1, 2, 3]).eval(&BigInt::new(10)), BigInt::new(321));
+        assert_eq!(poly(&[5, 3, 0, 2]).derivative(), poly(&[3, 0, 6]));
+    }
+
+    #[test]
+    fn test_div_rem() {
+        // x^3 - 2x^2 - 4 = (x - 3)(x^2 + x + 3) + 5
+        let p = poly(&[-4, 0, -2, 1]);
+        let (q, r) = p.div_rem(&poly(&[-3, 1])).unwrap();
+        assert_eq!(q, poly(&[3, 1, 1]));
+        assert_eq!(r, poly(&[5]));
+        assert_eq!(&(&q * &poly(&[-3, 1])) + &r, p);
+        assert_eq!(p.div_rem(&poly(&[1, 2])), None);
+        assert_eq!(
+            upoly(&[2, 3, 1]).div_rem(&upoly(&[1, 1])),
+            Some((upoly(&[2, 1]), upoly(&[])))
+        );
+    }
+}
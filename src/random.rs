@@ -0,0 +1,89 @@
+// Random Numbers
+// ==============
+
+// A small pseudo-random number generator, for the modules that need randomness: key generation in
+// `rsa`, and the random sample in `streaming`. We do not want to pull in a dependency for that, so
+// we use SplitMix64: a 64-bit counter, scrambled by a few multiplications and shifts. It is fast
+// and statistically decent, and with a fixed seed, tests are reproducible. It is *not*
+// cryptographically secure.
+use part05::BigInt;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    // The standard library seeds the hashers of every `HashMap` randomly, so we borrow a seed from
+    // there.
+    pub fn from_entropy() -> Self {
+        Rng::new(RandomState::new().build_hasher().finish())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A uniformly random number below `2^bits`.
+    pub fn gen_bits(&mut self, bits: usize) -> BigInt {
+        let mut data: Vec<u64> = (0..bits.div_ceil(64)).map(|_| self.next_u64()).collect();
+        if !bits.is_multiple_of(64) {
+            *data.last_mut().unwrap() >>= 64 - bits % 64;
+        }
+        BigInt::from_vec(data)
+    }
+
+    // A random prime with exactly `bits` bits. We set the top two bits, so that the product of two
+    // such primes has exactly twice as many bits, and the lowest bit, so that we only try odd
+    // candidates.
+    pub fn gen_prime(&mut self, bits: usize) -> BigInt {
+        assert!(bits >= 2, "there are no primes with fewer than 2 bits");
+        if bits == 2 {
+            return BigInt::new(2 + (self.next_u64() & 1));
+        }
+        let fixed = &(BigInt::new(3) << (bits - 2)) + &BigInt::one();
+        loop {
+            let candidate = &fixed + &(self.gen_bits(bits - 3) << 1);
+            if candidate.is_probable_prime() {
+                return candidate;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reproducible() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        let xs: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        let ys: Vec<u64> = (0..5).map(|_| b.next_u64()).collect();
+        assert_eq!(xs, ys);
+        assert_ne!(xs[0], xs[1]);
+        assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+    }
+
+    #[test]
+    fn test_bits_and_primes() {
+        let mut rng = Rng::new(7);
+        for bits in [1, 63, 64, 65, 130] {
+            assert!(rng.gen_bits(bits).bits() <= bits);
+        }
+        for bits in [2, 3, 40, 100] {
+            let p = rng.gen_prime(bits);
+            assert_eq!(p.bits(), bits);
+            assert!(p.is_probable_prime());
+        }
+    }
+}
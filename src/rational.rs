@@ -0,0 +1,423 @@
+// Exact Rational Numbers
+// ======================
+
+// A `BigRational` is a fraction `num / den` of big integers. We keep it in lowest terms with a
+// positive denominator, so every rational number has exactly one representation.
+use arith::ParseBigIntError;
+use bigdecimal::BigDecimal;
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::cmp::Ordering;
+use std::{f64, fmt, ops, str};
+
+#[derive(Clone, PartialEq, Eq)]
+pub struct BigRational {
+    num: SignedBigInt,
+    den: BigInt,
+}
+
+impl BigRational {
+    // Builds the fraction `num / den`, reducing it with the gcd. Returns `None` if `den` is zero.
+    pub fn new(num: SignedBigInt, den: SignedBigInt) -> Option<Self> {
+        if den.is_zero() {
+            return None;
+        }
+        let negative = num.is_negative() != den.is_negative();
+        let g = num.gcd(&den);
+        let num = num.into_magnitude() / &g;
+        let den = den.into_magnitude() / &g;
+        Some(BigRational {
+            num: SignedBigInt::new(negative, num),
+            den,
+        })
+    }
+
+    pub fn from_integer(n: SignedBigInt) -> Self {
+        BigRational {
+            num: n,
+            den: BigInt::one(),
+        }
+    }
+
+    pub fn zero() -> Self {
+        BigRational::from_integer(SignedBigInt::zero())
+    }
+
+    pub fn one() -> Self {
+        BigRational::from_integer(SignedBigInt::one())
+    }
+
+    pub fn numer(&self) -> &SignedBigInt {
+        &self.num
+    }
+
+    pub fn denom(&self) -> &BigInt {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == BigInt::one()
+    }
+
+    pub fn abs(&self) -> BigRational {
+        BigRational {
+            num: self.num.abs(),
+            den: self.den.clone(),
+        }
+    }
+
+    // The multiplicative inverse. Returns `None` for zero.
+    pub fn recip(&self) -> Option<BigRational> {
+        BigRational::new(SignedBigInt::from(self.den.clone()), self.num.clone())
+    }
+
+    // The largest integer that is not larger than `self`.
+    pub fn floor(&self) -> SignedBigInt {
+        self.num.div_rem_euclid(&self.den).unwrap().0
+    }
+
+    // ## Conversion to `f64`
+
+    // Returns the `f64` closest to `self`, rounding ties to even like the float parser does.
+    pub fn to_f64(&self) -> f64 {
+        if self.is_zero() {
+            return 0.0;
+        }
+        let n = self.num.magnitude();
+        let d = &self.den;
+        // We scale the quotient such that it has 54 or 55 bits: enough for the 53 bits of an
+        // `f64` mantissa, plus a rounding bit. The value is then `q * 2^-shift`.
+        let shift = 54 - (n.bits() as i64 - d.bits() as i64);
+        let (q, r) = if shift >= 0 {
+            (n << shift as usize).div_rem(d).unwrap()
+        } else {
+            n.div_rem(&(d << (-shift) as usize)).unwrap()
+        };
+        // Drop all bits below the 53 most significant ones. Subnormal numbers have less precision,
+        // because their lowest bit is fixed at 2^-1074.
+        let mut drop = q.bits() as i64 - 53;
+        if drop - shift < -1074 {
+            drop = shift - 1074;
+        }
+        let drop = drop as usize;
+        let mut mantissa = (&q >> drop).to_u64().unwrap_or(0);
+        if drop > 0 && q.bit(drop - 1) {
+            // We are at least half-way to the next value: round up, unless this is exactly a tie
+            // and we are already even.
+            let sticky = !r.is_zero() || (0..drop - 1).any(|i| q.bit(i));
+            if sticky || mantissa % 2 == 1 {
+                mantissa += 1;
+            }
+        }
+        let result = ldexp(mantissa as f64, drop as i64 - shift);
+        if self.num.is_negative() {
+            -result
+        } else {
+            result
+        }
+    }
+
+    // ## Continued fractions
+
+    // Expands `self` into its continued fraction `[a0; a1, a2, ...]`, i.e.,
+    // `a0 + 1 / (a1 + 1 / (a2 + ...))`. All terms except `a0` are positive.
+    pub fn continued_fraction(&self) -> Vec<SignedBigInt> {
+        let mut terms = vec![];
+        let mut n = self.num.clone();
+        let mut d = self.den.clone();
+        while !d.is_zero() {
+            let (a, r) = n.div_rem_euclid(&d).unwrap();
+            terms.push(a);
+            n = SignedBigInt::from(d);
+            d = r;
+        }
+        terms
+    }
+
+    // Evaluates a continued fraction. Returns `None` for an empty slice, or if a term makes a
+    // denominator vanish.
+    pub fn from_continued_fraction(terms: &[SignedBigInt]) -> Option<BigRational> {
+        let (last, rest) = terms.split_last()?;
+        let mut value = BigRational::from_integer(last.clone());
+        for a in rest.iter().rev() {
+            value = &BigRational::from_integer(a.clone()) + &value.recip()?;
+        }
+        Some(value)
+    }
+
+    // Returns the closest fraction to `self` whose denominator is at most `max_den`. These best
+    // approximations are among the convergents of the continued fraction and the semiconvergents
+    // right before the bound is hit, so we walk along the expansion until the next convergent
+    // would be too large. This is the same algorithm as Python's `Fraction.limit_denominator`.
+    pub fn approximate(&self, max_den: &BigInt) -> Option<BigRational> {
+        if max_den.is_zero() {
+            return None;
+        }
+        if self.den <= *max_den {
+            return Some(self.clone());
+        }
+        // `p0/q0` and `p1/q1` are the last two convergents.
+        let (mut p0, mut q0) = (SignedBigInt::zero(), BigInt::one());
+        let (mut p1, mut q1) = (SignedBigInt::one(), BigInt::zero());
+        let mut n = self.num.clone();
+        let mut d = self.den.clone();
+        loop {
+            let (a, r) = n.div_rem_euclid(&d).unwrap();
+            // Only the first term can be negative, and then `q1` is still zero.
+            let q2 = &q0 + &(a.magnitude() * &q1);
+            if q2 > *max_den {
+                break;
+            }
+            let p2 = &p0 + &(&a * &p1);
+            p0 = p1;
+            q0 = q1;
+            p1 = p2;
+            q1 = q2;
+            n = SignedBigInt::from(d);
+            d = r;
+        }
+        // The best semiconvergent uses the largest `k` that keeps the denominator in bounds.
+        let k = ((max_den - &q0).unwrap()) / &q1;
+        let semi_num = &p0 + &(&SignedBigInt::from(k.clone()) * &p1);
+        let semi = BigRational::new(semi_num, SignedBigInt::from(&q0 + &(&k * &q1))).unwrap();
+        let conv = BigRational::new(p1, SignedBigInt::from(q1)).unwrap();
+        if (&conv - self).abs() <= (&semi - self).abs() {
+            Some(conv)
+        } else {
+            Some(semi)
+        }
+    }
+}
+
+// Computes `x * 2^exp`. We multiply in steps that stay in the normal range, so that a result that
+// is exactly representable (possibly as a subnormal) comes out exactly.
+fn ldexp(mut x: f64, mut exp: i64) -> f64 {
+    let step = 2f64.powi(1000);
+    while exp > 1000 {
+        x *= step;
+        exp -= 1000;
+    }
+    while exp < -1000 {
+        x /= step;
+        exp += 1000;
+    }
+    x * 2f64.powi(exp as i32)
+}
+
+impl From<SignedBigInt> for BigRational {
+    fn from(n: SignedBigInt) -> Self {
+        BigRational::from_integer(n)
+    }
+}
+
+impl From<i64> for BigRational {
+    fn from(n: i64) -> Self {
+        BigRational::from_integer(SignedBigInt::from(n))
+    }
+}
+
+// A decimal is a fraction with a power of ten as denominator, so this conversion is exact.
+impl From<BigDecimal> for BigRational {
+    fn from(d: BigDecimal) -> Self {
+        let den = SignedBigInt::from(BigInt::new(10).pow(d.scale()));
+        BigRational::new(d.mantissa().clone(), den).unwrap()
+    }
+}
+
+// ## Ordering
+
+impl Ord for BigRational {
+    fn cmp(&self, other: &BigRational) -> Ordering {
+        // Denominators are positive, so cross-multiplying preserves the order.
+        let lhs = &self.num * &SignedBigInt::from(other.den.clone());
+        let rhs = &other.num * &SignedBigInt::from(self.den.clone());
+        lhs.cmp(&rhs)
+    }
+}
+
+impl PartialOrd for BigRational {
+    fn partial_cmp(&self, other: &BigRational) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ## Arithmetic
+
+impl ops::Add<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: &BigRational) -> BigRational {
+        let num = &self.num * &SignedBigInt::from(rhs.den.clone())
+            + &rhs.num * &SignedBigInt::from(self.den.clone());
+        BigRational::new(num, SignedBigInt::from(&self.den * &rhs.den)).unwrap()
+    }
+}
+
+impl ops::Sub<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: &BigRational) -> BigRational {
+        self + &(-rhs)
+    }
+}
+
+impl ops::Mul<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: &BigRational) -> BigRational {
+        let den = SignedBigInt::from(&self.den * &rhs.den);
+        BigRational::new(&self.num * &rhs.num, den).unwrap()
+    }
+}
+
+// Like the integer types, dividing by zero panics.
+impl ops::Div<&BigRational> for &BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: &BigRational) -> BigRational {
+        let num = &self.num * &SignedBigInt::from(rhs.den.clone());
+        let den = &rhs.num * &SignedBigInt::from(self.den.clone());
+        BigRational::new(num, den).expect("attempt to divide by zero")
+    }
+}
+
+forward_binop!(BigRational, Add, add);
+forward_binop!(BigRational, Sub, sub);
+forward_binop!(BigRational, Mul, mul);
+forward_binop!(BigRational, Div, div);
+
+impl ops::Neg for BigRational {
+    type Output = BigRational;
+    fn neg(self) -> BigRational {
+        BigRational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl ops::Neg for &BigRational {
+    type Output = BigRational;
+    fn neg(self) -> BigRational {
+        -self.clone()
+    }
+}
+
+// ## Formatting and parsing
+
+// Integers are printed without a denominator, everything else as `num/den`.
+impl fmt::Display for BigRational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl fmt::Debug for BigRational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParseRationalError {
+    Invalid(ParseBigIntError),
+    ZeroDenominator,
+}
+
+impl fmt::Display for ParseRationalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRationalError::Invalid(e) => e.fmt(f),
+            ParseRationalError::ZeroDenominator => write!(f, "denominator is zero"),
+        }
+    }
+}
+
+impl From<ParseBigIntError> for ParseRationalError {
+    fn from(e: ParseBigIntError) -> Self {
+        ParseRationalError::Invalid(e)
+    }
+}
+
+// We accept fractions like `-3/4` as well as decimals like `-0.75`.
+impl str::FromStr for BigRational {
+    type Err = ParseRationalError;
+    fn from_str(s: &str) -> Result<BigRational, ParseRationalError> {
+        match s.find('/') {
+            Some(i) => {
+                let num: SignedBigInt = s[..i].parse()?;
+                let den: SignedBigInt = s[i + 1..].parse()?;
+                BigRational::new(num, den).ok_or(ParseRationalError::ZeroDenominator)
+            }
+            None => Ok(BigRational::from(s.parse::<BigDecimal>()?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rat(s: &str) -> BigRational {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_arith() {
+        assert_eq!(rat("6/-8").to_string(), "-3/4");
+        assert_eq!(rat("-0.75"), rat("-3/4"));
+        assert_eq!((rat("1/2") + rat("1/3")).to_string(), "5/6");
+        assert_eq!((rat("1/2") - rat("5/6")).to_string(), "-1/3");
+        assert_eq!((rat("2/3") * rat("9/4")).to_string(), "3/2");
+        assert_eq!((rat("2/3") / rat("-4")).to_string(), "-1/6");
+        assert_eq!(rat("7/3").floor(), SignedBigInt::from(2));
+        assert_eq!(rat("-7/3").floor(), SignedBigInt::from(-3));
+        assert!(rat("-1/2") < rat("-1/3"));
+        assert_eq!(
+            "1/0".parse::<BigRational>(),
+            Err(ParseRationalError::ZeroDenominator)
+        );
+        assert!("1/x".parse::<BigRational>().is_err());
+    }
+
+    #[test]
+    fn test_to_f64() {
+        assert_eq!(rat("1/3").to_f64(), 1.0 / 3.0);
+        assert_eq!(rat("-0.1").to_f64(), -0.1);
+        assert_eq!(rat("123456789/1000").to_f64(), 123456.789);
+        // 2^53 + 1 is a tie between two floats, and rounds to the even one.
+        assert_eq!(rat("9007199254740993").to_f64(), 9007199254740992.0);
+        let tiny = BigRational::new(
+            SignedBigInt::one(),
+            SignedBigInt::from(BigInt::one() << 1074),
+        );
+        assert_eq!(tiny.unwrap().to_f64(), 5e-324);
+        let huge = BigRational::from_integer(SignedBigInt::from(BigInt::one() << 1024));
+        assert_eq!(huge.to_f64(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_continued_fraction() {
+        let terms = rat("415/93").continued_fraction();
+        let expected: Vec<_> = [4, 2, 6, 7]
+            .iter()
+            .map(|&a| SignedBigInt::from(a))
+            .collect();
+        assert_eq!(terms, expected);
+        assert_eq!(
+            BigRational::from_continued_fraction(&terms),
+            Some(rat("415/93"))
+        );
+        assert_eq!(rat("-1/2").continued_fraction()[0], SignedBigInt::from(-1));
+
+        let pi = rat("3.141592653589793");
+        assert_eq!(pi.approximate(&BigInt::new(10)), Some(rat("22/7")));
+        assert_eq!(pi.approximate(&BigInt::new(1000)), Some(rat("355/113")));
+        assert_eq!(pi.approximate(&BigInt::new(1)), Some(rat("3")));
+        assert_eq!(rat("-0.3").approximate(&BigInt::new(2)), Some(rat("-1/2")));
+    }
+}
@@ -0,0 +1,393 @@
+// RSA, the Toy Version
+// ====================
+
+// RSA is the classic example of big integers at work: key generation needs random primes and a
+// modular inverse, and encryption is a single `mod_pow`. This module implements it end to end,
+// for teaching and for testing our arithmetic against other implementations.
+//
+// It is a *toy*. The random number generator of `random` is not cryptographically secure, and
+// nothing here runs in constant time. Do not use it to protect anything.
+use arith::ParseBigIntError;
+use part05::BigInt;
+use random::Rng;
+use std::{fmt, str};
+
+// ## Keys
+
+// The public key is the modulus `n = p * q` and the exponent `e`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PublicKey {
+    n: BigInt,
+    e: BigInt,
+}
+
+// Besides the secret exponent `d` and the primes, the private key stores the values that make
+// decryption via the Chinese Remainder Theorem fast: `dp = d mod (p-1)`, `dq = d mod (q-1)` and
+// `qinv = q^-1 mod p`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivateKey {
+    public: PublicKey,
+    d: BigInt,
+    p: BigInt,
+    q: BigInt,
+    dp: BigInt,
+    dq: BigInt,
+    qinv: BigInt,
+}
+
+// The usual choice of public exponent: it is prime, and has only two bits set, so encryption is
+// cheap.
+pub const DEFAULT_EXPONENT: u64 = 65537;
+
+impl PublicKey {
+    pub fn new(n: BigInt, e: BigInt) -> Self {
+        PublicKey { n, e }
+    }
+
+    pub fn n(&self) -> &BigInt {
+        &self.n
+    }
+
+    pub fn e(&self) -> &BigInt {
+        &self.e
+    }
+
+    // The length of the modulus in bytes. Signatures have exactly this length.
+    pub fn size(&self) -> usize {
+        self.n.bits().div_ceil(8)
+    }
+
+    // Computes `m^e mod n`. Returns `None` if `m` is not below `n`, since it could then not be
+    // recovered.
+    pub fn encrypt(&self, m: &BigInt) -> Option<BigInt> {
+        if *m >= self.n {
+            return None;
+        }
+        Some(m.mod_pow(&self.e, &self.n))
+    }
+
+    // Checks a signature produced by `PrivateKey::sign` for the same message.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        if signature.len() != self.size() {
+            return false;
+        }
+        let s = BigInt::from_bytes_be(signature);
+        let expected = match pad_for_signature(message, self.size()) {
+            Some(em) => em,
+            None => return false,
+        };
+        match self.encrypt(&s) {
+            Some(m) => to_bytes_padded(&m, self.size()) == expected,
+            None => false,
+        }
+    }
+}
+
+impl PrivateKey {
+    // Generates a key whose modulus has exactly `bits` bits, with the default public exponent.
+    pub fn generate(bits: usize, rng: &mut Rng) -> PrivateKey {
+        assert!(bits >= 32, "the modulus has to be larger than the exponent");
+        let e = BigInt::new(DEFAULT_EXPONENT);
+        loop {
+            let p = rng.gen_prime(bits - bits / 2);
+            let q = rng.gen_prime(bits / 2);
+            // This fails if the primes are equal, or `e` has no inverse; then we just try again.
+            if let Some(key) = PrivateKey::from_primes(p, q, e.clone()) {
+                return key;
+            }
+        }
+    }
+
+    // Builds the key for the given primes and public exponent. We compute `d` as the inverse of
+    // `e` modulo `lcm(p-1, q-1)`, which is the smallest exponent that works. Returns `None` if the
+    // primes are equal or `e` is not invertible.
+    pub fn from_primes(p: BigInt, q: BigInt, e: BigInt) -> Option<PrivateKey> {
+        let one = BigInt::one();
+        let (p_1, q_1) = ((&p - &one)?, (&q - &one)?);
+        if p_1.is_zero() || q_1.is_zero() {
+            return None;
+        }
+        let lambda = &(&p_1 * &q_1) / &p_1.gcd(&q_1);
+        let d = e.mod_inverse(&lambda)?;
+        PrivateKey::from_parts(p, q, e, d)
+    }
+
+    // Builds the key from all its parts, checking that they fit together: the primes have to
+    // differ, and `e * d = 1` modulo both `p-1` and `q-1`.
+    fn from_parts(p: BigInt, q: BigInt, e: BigInt, d: BigInt) -> Option<PrivateKey> {
+        let one = BigInt::one();
+        let (p_1, q_1) = ((&p - &one)?, (&q - &one)?);
+        if p == q || p_1.is_zero() || q_1.is_zero() {
+            return None;
+        }
+        let ed = &e * &d;
+        if &ed % &p_1 != one || &ed % &q_1 != one {
+            return None;
+        }
+        Some(PrivateKey {
+            public: PublicKey::new(&p * &q, e),
+            dp: &d % &p_1,
+            dq: &d % &q_1,
+            qinv: q.mod_inverse(&p)?,
+            d,
+            p,
+            q,
+        })
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public
+    }
+
+    pub fn d(&self) -> &BigInt {
+        &self.d
+    }
+
+    // Computes `c^d mod n`. Instead of one exponentiation modulo `n`, we do two modulo `p` and `q`
+    // with exponents half as long, and combine them with Garner's formula
+    // `m = m2 + q * (qinv * (m1 - m2) mod p)`. That is about four times faster.
+    pub fn decrypt(&self, c: &BigInt) -> Option<BigInt> {
+        if *c >= self.public.n {
+            return None;
+        }
+        let m1 = c.mod_pow(&self.dp, &self.p);
+        let m2 = c.mod_pow(&self.dq, &self.q);
+        // Adding `p` keeps the difference non-negative.
+        let diff = (&(&m1 + &self.p) - &(&m2 % &self.p)).unwrap();
+        let h = &(&self.qinv * &diff) % &self.p;
+        Some(&m2 + &(&h * &self.q))
+    }
+
+    // Signs the message in the style of PKCS#1 v1.5. The standard signs a hash of the message,
+    // prefixed with an identifier of the hash function. Since the standard library has no hash
+    // functions, we sign the bytes exactly as given, and leave hashing to the caller. Returns
+    // `None` if the message is too long for the key.
+    pub fn sign(&self, message: &[u8]) -> Option<Vec<u8>> {
+        let k = self.public.size();
+        let em = BigInt::from_bytes_be(&pad_for_signature(message, k)?);
+        Some(to_bytes_padded(&self.decrypt(&em)?, k))
+    }
+}
+
+// The PKCS#1 v1.5 signature padding `00 01 FF ... FF 00 message`, filling `k` bytes. There have to
+// be at least eight `FF` bytes.
+fn pad_for_signature(message: &[u8], k: usize) -> Option<Vec<u8>> {
+    if message.len() + 11 > k {
+        return None;
+    }
+    let mut em = vec![0x00, 0x01];
+    em.resize(k - message.len() - 1, 0xff);
+    em.push(0x00);
+    em.extend_from_slice(message);
+    Some(em)
+}
+
+// The big-endian bytes of `x`, with leading zeros added to get exactly `len` bytes.
+fn to_bytes_padded(x: &BigInt, len: usize) -> Vec<u8> {
+    let bytes = x.to_bytes_be();
+    let mut result = vec![0; len.saturating_sub(bytes.len())];
+    result.extend(bytes);
+    result
+}
+
+// ## Text format
+//
+// Keys are stored as a header line, followed by one `name = value` line per number, in decimal.
+// Empty lines and lines starting with `#` are ignored, and the fields may come in any order. A
+// public key looks like this:
+//
+// ```
+// rsa-public-key
+// n = 3233
+// e = 17
+// ```
+//
+// A private key has the header `rsa-private-key` and the fields `n`, `e`, `d`, `p` and `q`. The
+// values for CRT decryption are recomputed when the key is read.
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseKeyError {
+    MissingHeader,
+    MalformedLine(String),
+    UnknownField(String),
+    DuplicateField(&'static str),
+    MissingField(&'static str),
+    InvalidNumber(ParseBigIntError),
+    // The numbers parsed, but do not form a valid key.
+    InconsistentKey,
+}
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseKeyError::MissingHeader => write!(f, "missing key header"),
+            ParseKeyError::MalformedLine(l) => write!(f, "malformed line: {}", l),
+            ParseKeyError::UnknownField(n) => write!(f, "unknown field: {}", n),
+            ParseKeyError::DuplicateField(n) => write!(f, "duplicate field: {}", n),
+            ParseKeyError::MissingField(n) => write!(f, "missing field: {}", n),
+            ParseKeyError::InvalidNumber(e) => e.fmt(f),
+            ParseKeyError::InconsistentKey => write!(f, "inconsistent key"),
+        }
+    }
+}
+
+impl From<ParseBigIntError> for ParseKeyError {
+    fn from(e: ParseBigIntError) -> Self {
+        ParseKeyError::InvalidNumber(e)
+    }
+}
+
+// Reads the fields `names` after the given header, and returns their values in the same order.
+fn parse_fields(
+    s: &str,
+    header: &str,
+    names: &[&'static str],
+) -> Result<Vec<BigInt>, ParseKeyError> {
+    let mut lines = s
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    if lines.next() != Some(header) {
+        return Err(ParseKeyError::MissingHeader);
+    }
+    let mut values: Vec<Option<BigInt>> = vec![None; names.len()];
+    for line in lines {
+        let eq = line
+            .find('=')
+            .ok_or_else(|| ParseKeyError::MalformedLine(line.to_string()))?;
+        let (name, value) = (line[..eq].trim(), line[eq + 1..].trim());
+        let i = names
+            .iter()
+            .position(|&n| n == name)
+            .ok_or_else(|| ParseKeyError::UnknownField(name.to_string()))?;
+        if values[i].is_some() {
+            return Err(ParseKeyError::DuplicateField(names[i]));
+        }
+        values[i] = Some(value.parse()?);
+    }
+    values
+        .into_iter()
+        .zip(names)
+        .map(|(v, &name)| v.ok_or(ParseKeyError::MissingField(name)))
+        .collect()
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "rsa-public-key")?;
+        writeln!(f, "n = {}", self.n)?;
+        writeln!(f, "e = {}", self.e)
+    }
+}
+
+impl str::FromStr for PublicKey {
+    type Err = ParseKeyError;
+    fn from_str(s: &str) -> Result<PublicKey, ParseKeyError> {
+        let mut v = parse_fields(s, "rsa-public-key", &["n", "e"])?;
+        let (e, n) = (v.pop().unwrap(), v.pop().unwrap());
+        Ok(PublicKey::new(n, e))
+    }
+}
+
+impl fmt::Display for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "rsa-private-key")?;
+        writeln!(f, "n = {}", self.public.n)?;
+        writeln!(f, "e = {}", self.public.e)?;
+        writeln!(f, "d = {}", self.d)?;
+        writeln!(f, "p = {}", self.p)?;
+        writeln!(f, "q = {}", self.q)
+    }
+}
+
+impl str::FromStr for PrivateKey {
+    type Err = ParseKeyError;
+    fn from_str(s: &str) -> Result<PrivateKey, ParseKeyError> {
+        let v = parse_fields(s, "rsa-private-key", &["n", "e", "d", "p", "q"])?;
+        let (n, e, d, p, q) = (&v[0], &v[1], &v[2], &v[3], &v[4]);
+        let key = PrivateKey::from_parts(p.clone(), q.clone(), e.clone(), d.clone())
+            .ok_or(ParseKeyError::InconsistentKey)?;
+        if key.public.n != *n {
+            return Err(ParseKeyError::InconsistentKey);
+        }
+        Ok(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The textbook example: `p = 61`, `q = 53` and `e = 17`.
+    fn textbook_key() -> PrivateKey {
+        PrivateKey::from_primes(BigInt::new(61), BigInt::new(53), BigInt::new(17)).unwrap()
+    }
+
+    #[test]
+    fn test_textbook() {
+        let key = textbook_key();
+        assert_eq!(*key.public_key().n(), BigInt::new(3233));
+        // With `lcm(60, 52) = 780` instead of `60 * 52`, `d` is 413 rather than the often quoted
+        // 2753. Both work.
+        assert_eq!(*key.d(), BigInt::new(413));
+        let c = key.public_key().encrypt(&BigInt::new(65)).unwrap();
+        assert_eq!(c, BigInt::new(2790));
+        assert_eq!(key.decrypt(&c), Some(BigInt::new(65)));
+        assert_eq!(key.public_key().encrypt(&BigInt::new(3233)), None);
+    }
+
+    #[test]
+    fn test_generate_sign_verify() {
+        let mut rng = Rng::new(42);
+        let key = PrivateKey::generate(256, &mut rng);
+        let public = key.public_key();
+        assert_eq!(public.n().bits(), 256);
+
+        let m = rng.gen_bits(200);
+        assert_eq!(key.decrypt(&public.encrypt(&m).unwrap()), Some(m));
+
+        let signature = key.sign(b"attack at dawn").unwrap();
+        assert_eq!(signature.len(), 32);
+        assert!(public.verify(b"attack at dawn", &signature));
+        assert!(!public.verify(b"attack at dusk", &signature));
+        // 22 bytes of message plus 11 bytes of padding do not fit into 32 bytes.
+        assert_eq!(key.sign(&[0; 22]), None);
+    }
+
+    #[test]
+    fn test_text_format() {
+        let key = textbook_key();
+        let text = key.to_string();
+        assert_eq!(
+            text,
+            "rsa-private-key\nn = 3233\ne = 17\nd = 413\np = 61\nq = 53\n"
+        );
+        assert_eq!(text.parse::<PrivateKey>(), Ok(key.clone()));
+        // Comments, blank lines and any field order are fine. The textbook `d` is accepted, too.
+        let public: PublicKey = "# demo\nrsa-public-key\n\ne = 17\nn = 3233\n"
+            .parse()
+            .unwrap();
+        assert_eq!(public, *key.public_key());
+        let alt = "rsa-private-key\nn = 3233\ne = 17\nd = 2753\np = 61\nq = 53";
+        assert_eq!(
+            alt.parse::<PrivateKey>()
+                .unwrap()
+                .decrypt(&BigInt::new(2790)),
+            Some(BigInt::new(65))
+        );
+
+        assert_eq!(
+            "rsa-public-key\nn = 3233".parse::<PublicKey>(),
+            Err(ParseKeyError::MissingField("e"))
+        );
+        assert_eq!(
+            "n = 3233\ne = 17".parse::<PublicKey>(),
+            Err(ParseKeyError::MissingHeader)
+        );
+        let wrong_d = "rsa-private-key\nn = 3233\ne = 17\nd = 414\np = 61\nq = 53";
+        assert_eq!(
+            wrong_d.parse::<PrivateKey>(),
+            Err(ParseKeyError::InconsistentKey)
+        );
+    }
+}
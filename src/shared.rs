@@ -0,0 +1,224 @@
+// Copy-on-Write Shared BigInt
+// ===========================
+
+// Cloning a `BigInt` copies all its digits (see `clone_demo` in part 05), and part 06's `vec_min`
+// clones every element it looks at. If we put the number behind an `Rc` (part 12) or `Arc`
+// (part 13), a clone just bumps a reference count. The catch is mutation: we can only mutate a
+// shared number if nobody else sees the change. That is what `Rc::make_mut` gives us: it mutates
+// in place if we hold the only reference, and otherwise first makes a private copy.
+use part05::BigInt;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::{cmp, fmt, mem, ops};
+
+// `Rc` and `Arc` provide the same functions, but not through a common trait. So we define one.
+pub trait SharedStorage: Clone + ops::Deref<Target = BigInt> {
+    fn from_bigint(b: BigInt) -> Self;
+    fn make_mut(this: &mut Self) -> &mut BigInt;
+    fn ptr_eq(this: &Self, other: &Self) -> bool;
+    fn is_unique(this: &Self) -> bool;
+}
+
+impl SharedStorage for Rc<BigInt> {
+    fn from_bigint(b: BigInt) -> Self {
+        Rc::new(b)
+    }
+    fn make_mut(this: &mut Self) -> &mut BigInt {
+        Rc::make_mut(this)
+    }
+    fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Rc::ptr_eq(this, other)
+    }
+    fn is_unique(this: &Self) -> bool {
+        Rc::strong_count(this) == 1
+    }
+}
+
+impl SharedStorage for Arc<BigInt> {
+    fn from_bigint(b: BigInt) -> Self {
+        Arc::new(b)
+    }
+    fn make_mut(this: &mut Self) -> &mut BigInt {
+        Arc::make_mut(this)
+    }
+    fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(this, other)
+    }
+    fn is_unique(this: &Self) -> bool {
+        Arc::strong_count(this) == 1
+    }
+}
+
+// The derived `Clone` clones the pointer, not the number.
+#[derive(Clone)]
+pub struct Shared<P: SharedStorage> {
+    ptr: P,
+}
+
+// Use `RcBigInt` within a thread, and `ArcBigInt` to share numbers across threads.
+pub type RcBigInt = Shared<Rc<BigInt>>;
+pub type ArcBigInt = Shared<Arc<BigInt>>;
+
+impl<P: SharedStorage> Shared<P> {
+    pub fn new(b: BigInt) -> Self {
+        Shared {
+            ptr: P::from_bigint(b),
+        }
+    }
+
+    // Gives mutable access to the number, copying it first if it is shared. This is the only way
+    // to mutate, so no other copy will ever observe the change.
+    pub fn make_mut(&mut self) -> &mut BigInt {
+        P::make_mut(&mut self.ptr)
+    }
+
+    // Whether `self` and `other` use the same storage, i.e., no copy has been made.
+    pub fn shares_with(&self, other: &Self) -> bool {
+        P::ptr_eq(&self.ptr, &other.ptr)
+    }
+
+    pub fn is_unique(&self) -> bool {
+        P::is_unique(&self.ptr)
+    }
+
+    // Returns the number, copying it only if it is still shared.
+    pub fn into_inner(mut self) -> BigInt {
+        mem::replace(self.make_mut(), BigInt::zero())
+    }
+}
+
+// Reading works exactly like for a `BigInt`.
+impl<P: SharedStorage> ops::Deref for Shared<P> {
+    type Target = BigInt;
+    fn deref(&self) -> &BigInt {
+        &self.ptr
+    }
+}
+
+impl<P: SharedStorage> From<BigInt> for Shared<P> {
+    fn from(b: BigInt) -> Self {
+        Shared::new(b)
+    }
+}
+
+// ## Mutation
+
+// The compound assignments go through `make_mut`, so `a += b` only copies `a` if some clone of it
+// is still around, and otherwise adds in place.
+impl<P: SharedStorage> ops::AddAssign<&BigInt> for Shared<P> {
+    fn add_assign(&mut self, rhs: &BigInt) {
+        *self.make_mut() += rhs;
+    }
+}
+
+impl<P: SharedStorage> ops::MulAssign<&BigInt> for Shared<P> {
+    fn mul_assign(&mut self, rhs: &BigInt) {
+        *self.make_mut() *= rhs;
+    }
+}
+
+// Binary operators produce a fresh number anyway, so they just work on the shared values.
+impl<P: SharedStorage> ops::Add for &Shared<P> {
+    type Output = Shared<P>;
+    fn add(self, rhs: &Shared<P>) -> Shared<P> {
+        Shared::new(&**self + &**rhs)
+    }
+}
+
+impl<P: SharedStorage> ops::Mul for &Shared<P> {
+    type Output = Shared<P>;
+    fn mul(self, rhs: &Shared<P>) -> Shared<P> {
+        Shared::new(&**self * &**rhs)
+    }
+}
+
+// ## Comparison and formatting
+
+impl<P: SharedStorage> PartialEq for Shared<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.shares_with(other) || **self == **other
+    }
+}
+
+impl<P: SharedStorage> Eq for Shared<P> {}
+
+impl<P: SharedStorage> PartialOrd for Shared<P> {
+    fn partial_cmp(&self, other: &Self) -> Option<cmp::Ordering> {
+        (**self).partial_cmp(&**other)
+    }
+}
+
+impl<P: SharedStorage> fmt::Display for Shared<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+impl<P: SharedStorage> fmt::Debug for Shared<P> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+// ## `vec_min`, revisited
+
+// This is part 06's `vec_min`, and it still clones every element it visits. But now, cloning is
+// just a reference count increment.
+pub fn vec_min<P: SharedStorage>(v: &[Shared<P>]) -> Option<Shared<P>> {
+    let mut min: Option<Shared<P>> = None;
+    for e in v {
+        let e = e.clone();
+        min = Some(match min {
+            Some(n) => {
+                if *e < *n {
+                    e
+                } else {
+                    n
+                }
+            }
+            None => e,
+        })
+    }
+    min
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_copy_on_write() {
+        let a = RcBigInt::new(BigInt::new(1 << 63));
+        let mut b = a.clone();
+        assert!(b.shares_with(&a) && !a.is_unique());
+
+        // Mutating `b` makes it a copy, and leaves `a` untouched.
+        b += &BigInt::new(1 << 63);
+        assert!(!b.shares_with(&a) && a.is_unique());
+        assert_eq!(*a, BigInt::new(1 << 63));
+        assert_eq!(*b, BigInt::from_vec(vec![0, 1]));
+
+        // Now `b` is unique, so further mutation happens in place: the digits stay where they are.
+        // (With enough capacity, that is; `*=` needs a new buffer anyway.)
+        b.make_mut().data.reserve(4);
+        let before = b.data.as_ptr();
+        b += &BigInt::new(5);
+        assert_eq!(b.data.as_ptr(), before);
+        b *= &BigInt::new(3);
+        assert_eq!(b.to_string(), "55340232221128654863");
+        assert_eq!(b.into_inner(), BigInt::from_vec(vec![15, 3]));
+    }
+
+    #[test]
+    fn test_vec_min() {
+        let v: Vec<ArcBigInt> = vec![
+            Shared::new(BigInt::from_vec(vec![0, 1])),
+            Shared::new(BigInt::new(7)),
+            Shared::new(BigInt::new(42)),
+        ];
+        let min = vec_min(&v).unwrap();
+        assert!(min.shares_with(&v[1]));
+        assert_eq!(vec_min::<Rc<BigInt>>(&[]), None);
+        assert_eq!((&v[1] + &v[2]).to_string(), "49");
+    }
+}
@@ -0,0 +1,341 @@
+// Signed Big Integers
+// ===================
+
+// `BigInt` only holds natural numbers, which is why part 08 had to decide what to do about
+// negative differences. Here we pair a magnitude with a sign, to get the full integers.
+use arith::ParseBigIntError;
+use part05::BigInt;
+use std::cmp::Ordering;
+use std::{fmt, ops, str};
+
+// The invariant is that zero is never negative, so that every number has exactly one
+// representation, and the derived `PartialEq` is correct.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SignedBigInt {
+    negative: bool,
+    magnitude: BigInt,
+}
+
+impl SignedBigInt {
+    pub fn new(negative: bool, magnitude: BigInt) -> Self {
+        let negative = negative && !magnitude.is_zero();
+        SignedBigInt {
+            negative,
+            magnitude,
+        }
+    }
+
+    pub fn zero() -> Self {
+        SignedBigInt::from(BigInt::zero())
+    }
+
+    pub fn one() -> Self {
+        SignedBigInt::from(BigInt::one())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.magnitude.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_positive(&self) -> bool {
+        !self.negative && !self.is_zero()
+    }
+
+    pub fn magnitude(&self) -> &BigInt {
+        &self.magnitude
+    }
+
+    pub fn into_magnitude(self) -> BigInt {
+        self.magnitude
+    }
+
+    // Returns the number as a natural number, if it is not negative.
+    pub fn to_bigint(&self) -> Option<BigInt> {
+        if self.negative {
+            None
+        } else {
+            Some(self.magnitude.clone())
+        }
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        let m = self.magnitude.to_u64()?;
+        if self.negative {
+            if m <= i64::MAX as u64 + 1 {
+                Some((m as i64).wrapping_neg())
+            } else {
+                None
+            }
+        } else if m <= i64::MAX as u64 {
+            Some(m as i64)
+        } else {
+            None
+        }
+    }
+
+    pub fn abs(&self) -> SignedBigInt {
+        SignedBigInt::from(self.magnitude.clone())
+    }
+
+    // -1, 0 or 1, depending on the sign.
+    pub fn signum(&self) -> i32 {
+        if self.negative {
+            -1
+        } else if self.is_zero() {
+            0
+        } else {
+            1
+        }
+    }
+
+    pub fn is_even(&self) -> bool {
+        self.magnitude.is_even()
+    }
+
+    pub fn pow(&self, exp: u32) -> SignedBigInt {
+        SignedBigInt::new(self.negative && exp % 2 == 1, self.magnitude.pow(exp))
+    }
+
+    // Division rounding towards zero, like the primitive integers: the remainder takes the sign
+    // of the dividend. Returns `None` for a zero divisor.
+    pub fn div_rem(&self, divisor: &SignedBigInt) -> Option<(SignedBigInt, SignedBigInt)> {
+        let (q, r) = self.magnitude.div_rem(&divisor.magnitude)?;
+        Some((
+            SignedBigInt::new(self.negative != divisor.negative, q),
+            SignedBigInt::new(self.negative, r),
+        ))
+    }
+
+    // Division rounding towards negative infinity: the remainder is never negative.
+    pub fn div_rem_euclid(&self, divisor: &BigInt) -> Option<(SignedBigInt, BigInt)> {
+        let (q, r) = self.magnitude.div_rem(divisor)?;
+        if self.negative && !r.is_zero() {
+            let q = SignedBigInt::new(true, q + BigInt::one());
+            Some((q, (divisor - &r).unwrap()))
+        } else {
+            Some((SignedBigInt::new(self.negative, q), r))
+        }
+    }
+
+    // The representative of `self` modulo `m` in `0..m`. This is what modular arithmetic needs.
+    pub fn rem_euclid(&self, m: &BigInt) -> BigInt {
+        self.div_rem_euclid(m)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+
+    pub fn gcd(&self, other: &SignedBigInt) -> BigInt {
+        self.magnitude.gcd(&other.magnitude)
+    }
+
+    // The extended Euclidean algorithm: returns `(g, s, t)` with `g = gcd(self, other)` and
+    // `s * self + t * other = g`.
+    pub fn extended_gcd(&self, other: &SignedBigInt) -> (BigInt, SignedBigInt, SignedBigInt) {
+        // We maintain `r_i = s_i * self + t_i * other` for the last two remainders.
+        let (mut r0, mut r1) = (self.abs(), other.abs());
+        let (mut s0, mut s1) = (SignedBigInt::one(), SignedBigInt::zero());
+        let (mut t0, mut t1) = (SignedBigInt::zero(), SignedBigInt::one());
+        while !r1.is_zero() {
+            let (q, r) = r0.div_rem(&r1).unwrap();
+            let s = &s0 - &(&q * &s1);
+            let t = &t0 - &(&q * &t1);
+            r0 = r1;
+            r1 = r;
+            s0 = s1;
+            s1 = s;
+            t0 = t1;
+            t1 = t;
+        }
+        // We worked with the absolute values, so the signs of the coefficients need fixing.
+        if self.negative {
+            s0 = -s0;
+        }
+        if other.negative {
+            t0 = -t0;
+        }
+        (r0.into_magnitude(), s0, t0)
+    }
+}
+
+impl From<BigInt> for SignedBigInt {
+    fn from(magnitude: BigInt) -> Self {
+        SignedBigInt {
+            negative: false,
+            magnitude,
+        }
+    }
+}
+
+impl From<i64> for SignedBigInt {
+    fn from(x: i64) -> Self {
+        SignedBigInt::new(x < 0, BigInt::new(x.unsigned_abs()))
+    }
+}
+
+// ## Ordering
+
+impl Ord for SignedBigInt {
+    fn cmp(&self, other: &SignedBigInt) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, false) => self.magnitude.cmp(&other.magnitude),
+            (true, true) => other.magnitude.cmp(&self.magnitude),
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+        }
+    }
+}
+
+impl PartialOrd for SignedBigInt {
+    fn partial_cmp(&self, other: &SignedBigInt) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// ## Arithmetic
+
+// Adds two numbers given as sign and magnitude. Subtraction is addition with a flipped sign, so
+// this is the only place where we have to think about the cases.
+fn add_signed(a_neg: bool, a: &BigInt, b_neg: bool, b: &BigInt) -> SignedBigInt {
+    if a_neg == b_neg {
+        return SignedBigInt::new(a_neg, a + b);
+    }
+    // The signs differ, so we subtract the smaller magnitude from the larger one, which then also
+    // determines the sign. Since we checked the order, `Sub` cannot fail.
+    match a.cmp(b) {
+        Ordering::Less => SignedBigInt::new(b_neg, (b - a).unwrap()),
+        _ => SignedBigInt::new(a_neg, (a - b).unwrap()),
+    }
+}
+
+impl ops::Add<&SignedBigInt> for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn add(self, rhs: &SignedBigInt) -> SignedBigInt {
+        add_signed(self.negative, &self.magnitude, rhs.negative, &rhs.magnitude)
+    }
+}
+
+impl ops::Sub<&SignedBigInt> for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn sub(self, rhs: &SignedBigInt) -> SignedBigInt {
+        add_signed(
+            self.negative,
+            &self.magnitude,
+            !rhs.negative,
+            &rhs.magnitude,
+        )
+    }
+}
+
+impl ops::Mul<&SignedBigInt> for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn mul(self, rhs: &SignedBigInt) -> SignedBigInt {
+        SignedBigInt::new(
+            self.negative != rhs.negative,
+            &self.magnitude * &rhs.magnitude,
+        )
+    }
+}
+
+impl ops::Div<&SignedBigInt> for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn div(self, rhs: &SignedBigInt) -> SignedBigInt {
+        self.div_rem(rhs).expect("attempt to divide by zero").0
+    }
+}
+
+impl ops::Rem<&SignedBigInt> for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn rem(self, rhs: &SignedBigInt) -> SignedBigInt {
+        self.div_rem(rhs)
+            .expect("attempt to calculate the remainder with a divisor of zero")
+            .1
+    }
+}
+
+forward_binop!(SignedBigInt, Add, add);
+forward_binop!(SignedBigInt, Sub, sub);
+forward_binop!(SignedBigInt, Mul, mul);
+forward_binop!(SignedBigInt, Div, div);
+forward_binop!(SignedBigInt, Rem, rem);
+
+impl ops::Neg for SignedBigInt {
+    type Output = SignedBigInt;
+    fn neg(self) -> SignedBigInt {
+        SignedBigInt::new(!self.negative, self.magnitude)
+    }
+}
+
+impl ops::Neg for &SignedBigInt {
+    type Output = SignedBigInt;
+    fn neg(self) -> SignedBigInt {
+        -self.clone()
+    }
+}
+
+// ## Formatting and parsing
+
+impl fmt::Display for SignedBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, "", &self.magnitude.to_string())
+    }
+}
+
+impl fmt::Debug for SignedBigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl str::FromStr for SignedBigInt {
+    type Err = ParseBigIntError;
+    fn from_str(s: &str) -> Result<SignedBigInt, ParseBigIntError> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        Ok(SignedBigInt::new(negative, BigInt::parse_digits(digits)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn int(s: &str) -> SignedBigInt {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_arith() {
+        let a = int("-18446744073709551616");
+        let b = int("5");
+        assert_eq!((&a + &b).to_string(), "-18446744073709551611");
+        assert_eq!((&b - &a).to_string(), "18446744073709551621");
+        assert_eq!((&a * &b).to_string(), "-92233720368547758080");
+        assert_eq!(&a / &b, int("-3689348814741910323"));
+        assert_eq!(&a % &b, int("-1"));
+        assert_eq!(a.rem_euclid(b.magnitude()), BigInt::new(4));
+        assert_eq!(-int("0"), SignedBigInt::zero());
+        assert_eq!(int("-7").pow(3), SignedBigInt::from(-343));
+
+        let (x, y) = (int("240"), int("-46"));
+        let (g, s, t) = x.extended_gcd(&y);
+        assert_eq!(g, BigInt::new(2));
+        assert_eq!(&s * &x + &t * &y, SignedBigInt::from(g));
+    }
+
+    #[test]
+    fn test_cmp_parse() {
+        let mut v = vec![int("3"), int("-10"), int("+0"), int("-2")];
+        v.sort();
+        assert_eq!(v, vec![int("-10"), int("-2"), int("0"), int("3")]);
+        assert_eq!("-0".parse::<SignedBigInt>().unwrap().to_string(), "0");
+        assert!("--1".parse::<SignedBigInt>().is_err());
+        assert_eq!(int("-9223372036854775808").to_i64(), Some(i64::MIN));
+        assert_eq!(int("9223372036854775808").to_i64(), None);
+    }
+}
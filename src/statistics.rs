@@ -0,0 +1,416 @@
+// Descriptive Statistics
+// ======================
+
+// Once `read_vec` from part 03 has given us a vector of numbers, the minimum is rarely the only
+// thing we want to know. This module computes the usual summary statistics for integers, floats
+// and big integers alike. Integers have an exact value, so for them we compute means, medians and
+// variances exactly, as `BigRational`s: the mean of `1` and `2` is `3/2`, not `1.5` give or take a
+// rounding error, and no sum can overflow. Floats are already approximations; for them we compute
+// in `f64`, with compensated summation from the module `summation`. NaNs are left out of all
+// statistics, and only counted.
+use float::Float;
+use numeric::Number;
+use part05::BigInt;
+use rational::BigRational;
+use signed::SignedBigInt;
+use std::cmp::Ordering;
+use std::fmt;
+use summation::NeumaierSum;
+
+// ## Samples
+
+// What we need to know about a number to compute statistics of it, on top of what every `Number`
+// of the module `numeric` can do. Integers only need to say what their exact value is.
+pub trait Sample: Number {
+    // The exact value, for types that have one. Either all values of a type have one, or none.
+    fn to_rational(&self) -> Option<BigRational>;
+    fn to_f64(&self) -> f64 {
+        self.to_rational().unwrap().to_f64()
+    }
+    fn is_nan(&self) -> bool {
+        false
+    }
+}
+
+// Every primitive integer fits into an `i128`, and from there into a `SignedBigInt`.
+macro_rules! impl_sample_integer {
+    ($($t:ty),*) => {$(
+        impl Sample for $t {
+            fn to_rational(&self) -> Option<BigRational> {
+                let x = *self as i128;
+                let magnitude = BigInt::from(x.unsigned_abs());
+                Some(BigRational::from_integer(SignedBigInt::new(x < 0, magnitude)))
+            }
+        }
+    )*};
+}
+
+macro_rules! impl_sample_float {
+    ($($t:ty),*) => {$(
+        impl Sample for $t {
+            fn to_rational(&self) -> Option<BigRational> {
+                None
+            }
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+            fn is_nan(&self) -> bool {
+                Float::is_nan(*self)
+            }
+        }
+    )*};
+}
+
+impl_sample_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+impl_sample_float!(f32, f64);
+
+impl Sample for BigInt {
+    fn to_rational(&self) -> Option<BigRational> {
+        Some(BigRational::from_integer(SignedBigInt::from(self.clone())))
+    }
+}
+
+impl Sample for SignedBigInt {
+    fn to_rational(&self) -> Option<BigRational> {
+        Some(BigRational::from_integer(self.clone()))
+    }
+}
+
+// ## Values
+
+// A statistic: exact if the samples were, and a float otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Exact(BigRational),
+    Approx(f64),
+}
+
+impl Value {
+    fn of<T: Sample>(x: &T) -> Value {
+        match x.to_rational() {
+            Some(r) => Value::Exact(r),
+            None => Value::Approx(x.to_f64()),
+        }
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Value::Exact(r) => r.to_f64(),
+            Value::Approx(x) => *x,
+        }
+    }
+}
+
+// Fractions are followed by their decimal value, like `7/2 (3.5)`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Exact(r) if r.is_integer() => write!(f, "{}", r),
+            Value::Exact(r) => write!(f, "{} ({})", r, r.to_f64()),
+            Value::Approx(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+// The samples that take part, i.e., all but the NaNs, sorted.
+fn sorted<T: Sample>(v: &[T]) -> Vec<&T> {
+    let mut s: Vec<&T> = v.iter().filter(|x| !x.is_nan()).collect();
+    s.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    s
+}
+
+fn count(n: usize) -> BigRational {
+    BigRational::from(n as i64)
+}
+
+// ## Location
+
+pub fn mean<T: Sample>(v: &[T]) -> Option<Value> {
+    let values: Vec<&T> = v.iter().filter(|x| !x.is_nan()).collect();
+    let n = values.len();
+    if n == 0 {
+        return None;
+    }
+    if values[0].to_rational().is_some() {
+        let sum = values.iter().fold(BigRational::zero(), |acc, x| {
+            &acc + &x.to_rational().unwrap()
+        });
+        Some(Value::Exact(&sum / &count(n)))
+    } else {
+        let mut sum = NeumaierSum::new();
+        for x in values {
+            sum.add(x.to_f64());
+        }
+        Some(Value::Approx(sum.total() / n as f64))
+    }
+}
+
+// The middle value, or the mean of the two middle values.
+pub fn median<T: Sample>(v: &[T]) -> Option<Value> {
+    let s = sorted(v);
+    let n = s.len();
+    if n == 0 {
+        return None;
+    }
+    if n % 2 == 1 {
+        return Some(Value::of(s[n / 2]));
+    }
+    Some(match (Value::of(s[n / 2 - 1]), Value::of(s[n / 2])) {
+        (Value::Exact(a), Value::Exact(b)) => Value::Exact(&(&a + &b) / &count(2)),
+        (a, b) => Value::Approx(a.to_f64() / 2.0 + b.to_f64() / 2.0),
+    })
+}
+
+// All values that occur most often, in increasing order.
+pub fn modes<T: Sample>(v: &[T]) -> Vec<T> {
+    let s = sorted(v);
+    let mut runs: Vec<(&T, usize)> = vec![];
+    for x in s {
+        match runs.last_mut() {
+            Some((y, n)) if *y == x => *n += 1,
+            _ => runs.push((x, 1)),
+        }
+    }
+    let most = runs.iter().map(|r| r.1).max().unwrap_or(0);
+    runs.into_iter()
+        .filter(|r| r.1 == most)
+        .map(|r| r.0.clone())
+        .collect()
+}
+
+// The value below which `p` percent of the samples lie, for `0 <= p <= 100`, interpolating linearly
+// between neighbours (as spreadsheets do).
+pub fn percentile<T: Sample>(v: &[T], p: f64) -> Option<f64> {
+    let s = sorted(v);
+    if s.is_empty() || !(0.0..=100.0).contains(&p) {
+        return None;
+    }
+    let h = (s.len() - 1) as f64 * p / 100.0;
+    let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+    let (a, b) = (s[lo].to_f64(), s[hi].to_f64());
+    Some(a + (b - a) * (h - lo as f64))
+}
+
+// ## Spread
+
+// The mean squared distance from the mean. The population variance divides by `n`; the sample
+// variance divides by `n - 1`, which makes it an unbiased estimate when the data is a sample of a
+// larger population.
+fn variance_with<T: Sample>(v: &[T], correction: usize) -> Option<Value> {
+    let values: Vec<&T> = v.iter().filter(|x| !x.is_nan()).collect();
+    let n = values.len();
+    if n <= correction {
+        return None;
+    }
+    Some(match mean(v)? {
+        Value::Exact(m) => {
+            let sum = values.iter().fold(BigRational::zero(), |acc, x| {
+                let d = &x.to_rational().unwrap() - &m;
+                &acc + &(&d * &d)
+            });
+            Value::Exact(&sum / &count(n - correction))
+        }
+        Value::Approx(m) => {
+            let mut sum = NeumaierSum::new();
+            for x in values {
+                let d = x.to_f64() - m;
+                sum.add(d * d);
+            }
+            Value::Approx(sum.total() / (n - correction) as f64)
+        }
+    })
+}
+
+pub fn variance<T: Sample>(v: &[T]) -> Option<Value> {
+    variance_with(v, 0)
+}
+
+pub fn sample_variance<T: Sample>(v: &[T]) -> Option<Value> {
+    variance_with(v, 1)
+}
+
+// Square roots are rarely rational, so the standard deviation is always a float.
+pub fn std_dev<T: Sample>(v: &[T]) -> Option<f64> {
+    variance(v).map(|var| var.to_f64().sqrt())
+}
+
+pub fn sample_std_dev<T: Sample>(v: &[T]) -> Option<f64> {
+    sample_variance(v).map(|var| var.to_f64().sqrt())
+}
+
+// ## Histograms
+
+// `bins` buckets of equal width from the minimum to the maximum. Each bucket includes its lower
+// bound, and the last one also the maximum.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    pub lower: f64,
+    pub width: f64,
+    pub counts: Vec<usize>,
+}
+
+pub fn histogram<T: Sample>(v: &[T], bins: usize) -> Option<Histogram> {
+    let s = sorted(v);
+    if s.is_empty() || bins == 0 {
+        return None;
+    }
+    let (lower, upper) = (s[0].to_f64(), s[s.len() - 1].to_f64());
+    let width = (upper - lower) / bins as f64;
+    let mut counts = vec![0; bins];
+    for x in s {
+        let bin = if width > 0.0 {
+            ((x.to_f64() - lower) / width) as usize
+        } else {
+            0
+        };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    Some(Histogram {
+        lower,
+        width,
+        counts,
+    })
+}
+
+// One line per bucket, with a bar of up to 40 `#`s.
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let most = self.counts.iter().max().cloned().unwrap_or(0).max(1);
+        for (i, &n) in self.counts.iter().enumerate() {
+            let lo = self.lower + self.width * i as f64;
+            let bar = "#".repeat((n * 40).div_ceil(most));
+            writeln!(
+                f,
+                "{:>12.4} .. {:>12.4} | {:>6} {}",
+                lo,
+                lo + self.width,
+                n,
+                bar
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// ## Summary report
+
+#[derive(Clone, Debug)]
+pub struct Summary<T> {
+    pub count: usize,
+    pub nans: usize,
+    pub min: Option<T>,
+    pub max: Option<T>,
+    pub mean: Option<Value>,
+    pub median: Option<Value>,
+    pub std_dev: Option<f64>,
+}
+
+pub fn summary<T: Sample>(v: &[T]) -> Summary<T> {
+    let s = sorted(v);
+    Summary {
+        count: s.len(),
+        nans: v.len() - s.len(),
+        min: s.first().map(|&x| x.clone()),
+        max: s.last().map(|&x| x.clone()),
+        mean: mean(v),
+        median: median(v),
+        std_dev: sample_std_dev(v),
+    }
+}
+
+// One line of a summary, with `-` for a missing value. The module `streaming` uses it as well.
+pub fn write_opt<D: fmt::Display>(
+    f: &mut fmt::Formatter,
+    name: &str,
+    x: &Option<D>,
+) -> fmt::Result {
+    match x {
+        Some(x) => writeln!(f, "{:<8} {}", name, x),
+        None => writeln!(f, "{:<8} -", name),
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Summary<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:<8} {}", "count", self.count)?;
+        if self.nans > 0 {
+            writeln!(f, "{:<8} {}", "NaN", self.nans)?;
+        }
+        write_opt(f, "min", &self.min)?;
+        write_opt(f, "max", &self.max)?;
+        write_opt(f, "mean", &self.mean)?;
+        write_opt(f, "median", &self.median)?;
+        write_opt(f, "std dev", &self.std_dev)
+    }
+}
+
+// Prints the summary, followed by a histogram.
+pub fn print_report<T: Sample + fmt::Display>(v: &[T], bins: usize) {
+    print!("{}", summary(v));
+    if let Some(h) = histogram(v, bins) {
+        print!("{}", h);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact(num: i64, den: i64) -> Value {
+        Value::Exact(BigRational::new(SignedBigInt::from(num), SignedBigInt::from(den)).unwrap())
+    }
+
+    #[test]
+    fn test_exact() {
+        let v = [4, 1, 2, 2, 7, 5];
+        assert_eq!(mean(&v), Some(exact(7, 2)));
+        assert_eq!(median(&v), Some(exact(3, 1)));
+        assert_eq!(modes(&v), vec![2]);
+        // The distances from 7/2 are 1/2, 5/2, 3/2, 3/2, 7/2 and 3/2; their squares sum to 51/2.
+        assert_eq!(variance(&v), Some(exact(17, 4)));
+        assert_eq!(sample_variance(&v), Some(exact(51, 10)));
+        assert_eq!(
+            mean(&[u64::MAX, u64::MAX]).unwrap().to_f64(),
+            u64::MAX as f64
+        );
+        let big = [BigInt::from_vec(vec![0, 1]), BigInt::one()];
+        assert_eq!(
+            mean(&big).unwrap().to_string(),
+            "18446744073709551617/2 (9223372036854776000)"
+        );
+        assert_eq!(mean::<i32>(&[]), None);
+        assert_eq!(sample_variance(&[3]), None);
+    }
+
+    #[test]
+    fn test_floats() {
+        let v = [2.5, f64::NAN, 1.0, 4.0, 1.0];
+        assert_eq!(mean(&v), Some(Value::Approx(2.125)));
+        assert_eq!(median(&v), Some(Value::Approx(1.75)));
+        assert_eq!(modes(&v), vec![1.0]);
+        assert_eq!(percentile(&v, 0.0), Some(1.0));
+        assert_eq!(percentile(&v, 100.0), Some(4.0));
+        assert_eq!(percentile(&v, 50.0), Some(1.75));
+        assert_eq!(percentile(&v, 101.0), None);
+        assert_eq!(std_dev(&[1.0, 3.0]), Some(1.0));
+        let s = summary(&v);
+        assert_eq!(
+            (s.count, s.nans, s.min, s.max),
+            (4, 1, Some(1.0), Some(4.0))
+        );
+    }
+
+    #[test]
+    fn test_histogram_and_report() {
+        let v: Vec<u32> = (0..100).collect();
+        let h = histogram(&v, 4).unwrap();
+        assert_eq!(h.counts, vec![25, 25, 25, 25]);
+        assert_eq!(histogram(&[5, 5], 3).unwrap().counts, vec![2, 0, 0]);
+        assert!(h.to_string().lines().all(|l| l.ends_with(&"#".repeat(40))));
+        let report = summary(&[1, 2]).to_string();
+        assert_eq!(
+            report,
+            "count    2\nmin      1\nmax      2\nmean     3/2 (1.5)\nmedian   3/2 (1.5)\nstd dev  0.7071067811865476\n"
+        );
+    }
+}
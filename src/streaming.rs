@@ -0,0 +1,424 @@
+// Streaming Aggregation
+// =====================
+
+// `read_vec` in part 03 collects all input into a `Vec` before anything is computed, and the module
+// `statistics` sorts that vector. That works until the input is larger than memory, or never ends,
+// like the output of a running program piped into ours. The aggregators in this module look at
+// every number once, as it arrives, and keep only a fixed amount of state. Count, sum, minimum,
+// maximum, mean and variance come out exactly (up to float rounding); quantiles can only be
+// approximated in fixed memory, and a random sample gives an impression of the rest.
+use input::{parse_number, split_values};
+use random::Rng;
+use statistics::write_opt;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use summation::NeumaierSum;
+
+// ## Running statistics
+
+// The variance uses Welford's method: we update the mean and the sum of squared distances from it
+// with every number. The obvious alternative, `sum(x^2)/n - mean^2`, subtracts two large, nearly
+// equal numbers and can lose all precision, or even come out negative.
+#[derive(Clone, Debug, Default)]
+pub struct RunningStats {
+    count: u64,
+    nans: u64,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: NeumaierSum,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStats {
+    pub fn new() -> Self {
+        RunningStats::default()
+    }
+
+    // NaNs are counted, but otherwise left out, as in `statistics`.
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            self.nans += 1;
+            return;
+        }
+        self.count += 1;
+        self.min = Some(self.min.map_or(x, |m| m.min(x)));
+        self.max = Some(self.max.map_or(x, |m| m.max(x)));
+        self.sum.add(x);
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn nans(&self) -> u64 {
+        self.nans
+    }
+
+    pub fn min(&self) -> Option<f64> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<f64> {
+        self.max
+    }
+
+    pub fn sum(&self) -> f64 {
+        self.sum.total()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == 0 {
+            None
+        } else {
+            Some(self.mean)
+        }
+    }
+
+    pub fn variance(&self) -> Option<f64> {
+        self.mean().map(|_| self.m2 / self.count as f64)
+    }
+
+    pub fn sample_variance(&self) -> Option<f64> {
+        if self.count < 2 {
+            None
+        } else {
+            Some(self.m2 / (self.count - 1) as f64)
+        }
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.sample_variance().map(f64::sqrt)
+    }
+}
+
+// ## Reservoir sampling
+
+// A uniform random sample of `capacity` elements from a stream of unknown length (Algorithm R):
+// keep the first `capacity` elements, and then replace a random one of them with the `i`-th element
+// with probability `capacity / i`. At every point, each element seen so far is in the sample with the
+// same probability.
+pub struct Reservoir<T> {
+    capacity: usize,
+    seen: u64,
+    sample: Vec<T>,
+    rng: Rng,
+}
+
+impl<T> Reservoir<T> {
+    pub fn new(capacity: usize, rng: Rng) -> Self {
+        Reservoir {
+            capacity,
+            seen: 0,
+            sample: Vec::with_capacity(capacity),
+            rng,
+        }
+    }
+
+    pub fn push(&mut self, x: T) {
+        self.seen += 1;
+        if self.sample.len() < self.capacity {
+            self.sample.push(x);
+            return;
+        }
+        // The modulo is very slightly biased towards small values, which does not matter for
+        // streams shorter than 2^40 or so.
+        let j = self.rng.next_u64() % self.seen;
+        if (j as usize) < self.capacity {
+            self.sample[j as usize] = x;
+        }
+    }
+
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    pub fn sample(&self) -> &[T] {
+        &self.sample
+    }
+}
+
+// ## Approximate quantiles
+
+// The P² algorithm of Jain and Chlamtac estimates one quantile with five markers: the minimum, the
+// maximum, the quantile itself, and the two quantiles half-way to the ends. For every number, the
+// markers' positions (how many numbers are below them) move, and when a marker is more than one
+// position away from where it should be, its height is adjusted by fitting a parabola through it
+// and its neighbours.
+#[derive(Clone, Debug)]
+pub struct P2Quantile {
+    p: f64,
+    count: u64,
+    heights: Vec<f64>,
+    positions: [f64; 5],
+    desired: [f64; 5],
+    increments: [f64; 5],
+}
+
+impl P2Quantile {
+    // For `0 <= p <= 1`, e.g., `0.5` for the median.
+    pub fn new(p: f64) -> Self {
+        assert!((0.0..=1.0).contains(&p), "quantile must be between 0 and 1");
+        P2Quantile {
+            p,
+            count: 0,
+            heights: Vec::with_capacity(5),
+            positions: [1.0, 2.0, 3.0, 4.0, 5.0],
+            desired: [1.0, 1.0 + 2.0 * p, 1.0 + 4.0 * p, 3.0 + 2.0 * p, 5.0],
+            increments: [0.0, p / 2.0, p, (1.0 + p) / 2.0, 1.0],
+        }
+    }
+
+    pub fn quantile(&self) -> f64 {
+        self.p
+    }
+
+    pub fn push(&mut self, x: f64) {
+        if x.is_nan() {
+            return;
+        }
+        self.count += 1;
+        let q = &mut self.heights;
+        // The first five numbers become the markers.
+        if q.len() < 5 {
+            q.push(x);
+            q.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            return;
+        }
+        // Find the cell `k` with `q[k] <= x < q[k + 1]`, extending the ends if needed.
+        let k = if x < q[0] {
+            q[0] = x;
+            0
+        } else if x >= q[4] {
+            q[4] = x;
+            3
+        } else {
+            (0..4).find(|&i| x < q[i + 1]).unwrap()
+        };
+        for i in k + 1..5 {
+            self.positions[i] += 1.0;
+        }
+        for i in 0..5 {
+            self.desired[i] += self.increments[i];
+        }
+        let n = &mut self.positions;
+        for i in 1..4 {
+            let d = self.desired[i] - n[i];
+            if (d >= 1.0 && n[i + 1] - n[i] > 1.0) || (d <= -1.0 && n[i - 1] - n[i] < -1.0) {
+                let d = d.signum();
+                let parabolic = q[i]
+                    + d / (n[i + 1] - n[i - 1])
+                        * ((n[i] - n[i - 1] + d) * (q[i + 1] - q[i]) / (n[i + 1] - n[i])
+                            + (n[i + 1] - n[i] - d) * (q[i] - q[i - 1]) / (n[i] - n[i - 1]));
+                q[i] = if q[i - 1] < parabolic && parabolic < q[i + 1] {
+                    parabolic
+                } else {
+                    // The parabola overshoots, so we fall back to linear interpolation.
+                    let j = if d > 0.0 { i + 1 } else { i - 1 };
+                    q[i] + d * (q[j] - q[i]) / (n[j] - n[i])
+                };
+                n[i] += d;
+            }
+        }
+    }
+
+    // Up to five numbers, we still have them all, and interpolate between them. Only after that does
+    // the middle marker track the quantile.
+    pub fn estimate(&self) -> Option<f64> {
+        let q = &self.heights;
+        match q.len() {
+            0 => None,
+            _ if self.count > 5 => Some(q[2]),
+            len => {
+                let h = (len - 1) as f64 * self.p;
+                let lo = h.floor() as usize;
+                let hi = h.ceil() as usize;
+                Some(q[lo] + (q[hi] - q[lo]) * (h - lo as f64))
+            }
+        }
+    }
+}
+
+// ## Aggregating a stream of lines
+
+// Everything together, fed with lines of text. Lines that are not numbers are counted and skipped.
+pub struct Aggregator {
+    lines: u64,
+    bad_lines: u64,
+    stats: RunningStats,
+    quantiles: Vec<P2Quantile>,
+    sample: Reservoir<f64>,
+}
+
+impl Aggregator {
+    pub fn new(quantiles: &[f64], sample_size: usize, rng: Rng) -> Self {
+        Aggregator {
+            lines: 0,
+            bad_lines: 0,
+            stats: RunningStats::new(),
+            quantiles: quantiles.iter().map(|&p| P2Quantile::new(p)).collect(),
+            sample: Reservoir::new(sample_size, rng),
+        }
+    }
+
+    pub fn push(&mut self, x: f64) {
+        self.stats.push(x);
+        if !x.is_nan() {
+            for q in &mut self.quantiles {
+                q.push(x);
+            }
+            self.sample.push(x);
+        }
+    }
+
+    // A line holds numbers as in `input::Format::values()`: separated by spaces or commas, with
+    // `#` comments, and in any syntax `input::parse_number` accepts. Lines without numbers are
+    // ignored, and a line counts as bad if any of its numbers is.
+    pub fn push_line(&mut self, line: &str) {
+        let mut tokens = split_values(line, Some('#')).peekable();
+        if tokens.peek().is_none() {
+            return;
+        }
+        self.lines += 1;
+        let mut bad = false;
+        for token in tokens {
+            match parse_number::<f64>(token) {
+                Ok(x) => self.push(x),
+                Err(_) => bad = true,
+            }
+        }
+        if bad {
+            self.bad_lines += 1;
+        }
+    }
+
+    pub fn stats(&self) -> &RunningStats {
+        &self.stats
+    }
+
+    pub fn quantiles(&self) -> &[P2Quantile] {
+        &self.quantiles
+    }
+
+    pub fn sample(&self) -> &[f64] {
+        self.sample.sample()
+    }
+
+    pub fn bad_lines(&self) -> u64 {
+        self.bad_lines
+    }
+}
+
+impl fmt::Display for Aggregator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = &self.stats;
+        write!(f, "{:<8} {}", "count", s.count())?;
+        if s.nans() > 0 || self.bad_lines > 0 {
+            write!(f, " ({} NaN, {} bad lines)", s.nans(), self.bad_lines)?;
+        }
+        writeln!(f)?;
+        write_opt(f, "min", &s.min())?;
+        write_opt(f, "max", &s.max())?;
+        writeln!(f, "{:<8} {}", "sum", s.sum())?;
+        write_opt(f, "mean", &s.mean())?;
+        write_opt(f, "std dev", &s.std_dev())?;
+        for q in &self.quantiles {
+            // `0.07 * 100.0` is `7.000000000000001`, so we round away the float noise.
+            let percent = (q.quantile() * 100.0 * 1e6).round() / 1e6;
+            write_opt(f, &format!("p{} ~", percent), &q.estimate())?;
+        }
+        Ok(())
+    }
+}
+
+// Feeds all lines of `input` to `aggregator`, and writes a summary to `out` every `every` lines
+// (never, if it is zero) and at the end.
+pub fn aggregate<R: BufRead, W: Write>(
+    input: R,
+    aggregator: &mut Aggregator,
+    every: u64,
+    out: &mut W,
+) -> io::Result<()> {
+    for line in input.lines() {
+        aggregator.push_line(&line?);
+        if every > 0 && aggregator.lines > 0 && aggregator.lines.is_multiple_of(every) {
+            writeln!(out, "after {} lines:\n{}", aggregator.lines, aggregator)?;
+        }
+    }
+    write!(out, "{}", aggregator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_running_stats() {
+        let mut s = RunningStats::new();
+        assert_eq!((s.mean(), s.min()), (None, None));
+        for x in [2.0, 4.0, f64::NAN, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0] {
+            s.push(x);
+        }
+        assert_eq!((s.count(), s.nans()), (8, 1));
+        assert_eq!((s.min(), s.max(), s.sum()), (Some(2.0), Some(9.0), 40.0));
+        assert_eq!((s.mean(), s.variance()), (Some(5.0), Some(4.0)));
+        // Shifting by a large offset does not hurt Welford's method.
+        let mut shifted = RunningStats::new();
+        for x in [1e9 + 4.0, 1e9 + 7.0, 1e9 + 13.0, 1e9 + 16.0] {
+            shifted.push(x);
+        }
+        assert_eq!(shifted.variance(), Some(22.5));
+    }
+
+    #[test]
+    fn test_sketches() {
+        let mut reservoir = Reservoir::new(10, Rng::new(1));
+        let mut median = P2Quantile::new(0.5);
+        let mut p90 = P2Quantile::new(0.9);
+        let mut rng = Rng::new(7);
+        for _ in 0..10_000 {
+            let x = (rng.next_u64() % 1000) as f64;
+            reservoir.push(x);
+            median.push(x);
+            p90.push(x);
+        }
+        assert_eq!((reservoir.seen(), reservoir.sample().len()), (10_000, 10));
+        assert!((median.estimate().unwrap() - 500.0).abs() < 25.0);
+        assert!((p90.estimate().unwrap() - 900.0).abs() < 25.0);
+        let mut few = P2Quantile::new(0.5);
+        assert_eq!(few.estimate(), None);
+        few.push(3.0);
+        few.push(1.0);
+        assert_eq!(few.estimate(), Some(2.0));
+        let mut five = P2Quantile::new(0.9);
+        for x in 1..=5 {
+            five.push(x as f64);
+        }
+        assert_eq!(five.estimate(), Some(4.6));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let input = "3\n1\n\nfoo\n2\n4\n";
+        let mut aggregator = Aggregator::new(&[0.5], 2, Rng::new(3));
+        let mut out = vec![];
+        aggregate(input.as_bytes(), &mut aggregator, 2, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("after 2 lines:\ncount    2\n"));
+        assert!(out.contains("after 4 lines:\ncount    3 (0 NaN, 1 bad lines)\n"));
+        assert!(out.ends_with("mean     2.5\nstd dev  1.2909944487358056\np50 ~    2.5\n"));
+        assert_eq!(aggregator.sample().len(), 2);
+        // The same syntax as `input::Format::values()`.
+        let mut values = Aggregator::new(&[], 0, Rng::new(3));
+        for line in ["1, 0x2 # three", "# only a comment", "1_000 x"] {
+            values.push_line(line);
+        }
+        assert_eq!((values.stats().count(), values.stats().sum()), (3, 1003.0));
+        assert_eq!((values.lines, values.bad_lines()), (2, 1));
+        let labels = Aggregator::new(&[0.07, 0.999], 0, Rng::new(3)).to_string();
+        assert!(labels.contains("p7 ~") && labels.contains("p99.9 ~"));
+    }
+}
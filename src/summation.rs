@@ -0,0 +1,191 @@
+// Summation Without Surprises
+// ===========================
+
+// `vec_sum` in part 01 adds `i32`s with `s + e`. When the sum does not fit, that panics in a debug
+// build, and silently wraps around in a release build, turning a large positive sum negative. For
+// floats, nothing overflows that easily, but every addition rounds, and when adding many numbers
+// of different sizes, those rounding errors add up. This module has three answers: report the
+// overflow, avoid it by moving to big integers, or compensate for the rounding errors.
+use numeric::{CheckedAdd, Zero};
+use part05::BigInt;
+use signed::SignedBigInt;
+use std::{error, fmt};
+
+// ## Reporting overflow
+
+// The sum did not fit; adding the element at `index` was one step too many.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overflow {
+    pub index: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sum overflows at element {}", self.index)
+    }
+}
+
+impl error::Error for Overflow {}
+
+// Works for every type with the numeric traits, including floats, where overflowing means becoming
+// infinite.
+pub fn checked_sum<T, I>(iter: I) -> Result<T, Overflow>
+where
+    T: Zero + CheckedAdd,
+    I: IntoIterator<Item = T>,
+{
+    let mut sum = T::zero();
+    for (index, e) in iter.into_iter().enumerate() {
+        sum = sum.checked_add(&e).ok_or(Overflow { index })?;
+    }
+    Ok(sum)
+}
+
+// ## Promoting to big integers
+
+// Instead of giving up, we can also keep going with a type that cannot overflow. `SignedBigInt`
+// allocates, so we add up in an `i128` as long as that works, which for `i64` inputs is the first
+// 2^63 or so of them, i.e., always. Only when that overflows do we move the sum to a big integer.
+pub fn wide_sum<I>(iter: I) -> SignedBigInt
+where
+    I: IntoIterator,
+    I::Item: Into<i128>,
+{
+    let mut small: i128 = 0;
+    let mut big: Option<SignedBigInt> = None;
+    for e in iter {
+        let e = e.into();
+        match big {
+            Some(ref mut b) => *b = &*b + &signed_from_i128(e),
+            None => match small.checked_add(e) {
+                Some(s) => small = s,
+                None => big = Some(&signed_from_i128(small) + &signed_from_i128(e)),
+            },
+        }
+    }
+    big.unwrap_or_else(|| signed_from_i128(small))
+}
+
+fn signed_from_i128(x: i128) -> SignedBigInt {
+    SignedBigInt::new(x < 0, BigInt::from(x.unsigned_abs()))
+}
+
+// Unsigned sums go straight to `BigInt`, through a `u128` accumulator in the same way.
+pub fn wide_sum_unsigned<I>(iter: I) -> BigInt
+where
+    I: IntoIterator,
+    I::Item: Into<u128>,
+{
+    let mut small: u128 = 0;
+    let mut big: Option<BigInt> = None;
+    for e in iter {
+        let e = e.into();
+        match big {
+            Some(ref mut b) => *b += &BigInt::from(e),
+            None => match small.checked_add(e) {
+                Some(s) => small = s,
+                None => big = Some(&BigInt::from(small) + &BigInt::from(e)),
+            },
+        }
+    }
+    big.unwrap_or_else(|| BigInt::from(small))
+}
+
+// ## Compensated float summation
+
+// Adding a small number to a large one loses the low bits of the small one. Kahan's idea is to
+// compute what was lost, `(sum + x) - sum - x`, and add it back in with the next number. Neumaier's
+// variant also handles the case that the new number is larger than the sum so far, where Kahan's
+// version loses the low bits of the *sum* instead. The error then no longer grows with the number
+// of elements, and stays around one rounding error.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct NeumaierSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl NeumaierSum {
+    pub fn new() -> Self {
+        NeumaierSum::default()
+    }
+
+    pub fn add(&mut self, x: f64) {
+        let t = self.sum + x;
+        if self.sum.abs() >= x.abs() {
+            self.compensation += (self.sum - t) + x;
+        } else {
+            self.compensation += (x - t) + self.sum;
+        }
+        self.sum = t;
+    }
+
+    pub fn total(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+pub fn neumaier_sum<I>(iter: I) -> f64
+where
+    I: IntoIterator,
+    I::Item: Into<f64>,
+{
+    let mut sum = NeumaierSum::new();
+    for x in iter {
+        sum.add(x.into());
+    }
+    sum.total()
+}
+
+// Classic Kahan summation, for comparison. The compensation is subtracted from the next input.
+pub fn kahan_sum<I>(iter: I) -> f64
+where
+    I: IntoIterator,
+    I::Item: Into<f64>,
+{
+    let (mut sum, mut compensation) = (0.0f64, 0.0f64);
+    for x in iter {
+        let y = x.into() - compensation;
+        let t = sum + y;
+        compensation = (t - sum) - y;
+        sum = t;
+    }
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checked_and_wide() {
+        assert_eq!(checked_sum(vec![1, 2, 3]), Ok(6));
+        let v = vec![i32::MAX, 1, -5];
+        assert_eq!(checked_sum(v.clone()), Err(Overflow { index: 1 }));
+        assert_eq!(
+            Overflow { index: 1 }.to_string(),
+            "sum overflows at element 1"
+        );
+        assert_eq!(wide_sum(v), SignedBigInt::from(i32::MAX as i64 - 4));
+        // Beyond `i128`, and back below zero.
+        let huge = vec![i128::MAX, i128::MAX, 2, i128::MIN, i128::MIN];
+        assert_eq!(wide_sum(huge), SignedBigInt::from(0));
+        let total = wide_sum(vec![i128::MAX, 1]);
+        assert_eq!(total.to_string(), "170141183460469231731687303715884105728");
+        let unsigned = wide_sum_unsigned(vec![u128::MAX, 1]);
+        assert_eq!(unsigned, BigInt::from_vec(vec![0, 0, 1]));
+    }
+
+    #[test]
+    fn test_compensated() {
+        // Ten times 0.1 is not exactly 1.0 with naive summation.
+        let tenths = vec![0.1f64; 10];
+        assert_ne!(tenths.iter().sum::<f64>(), 1.0);
+        assert_eq!(kahan_sum(tenths.clone()), 1.0);
+        assert_eq!(neumaier_sum(tenths), 1.0);
+        // Here, Kahan loses the small terms against the large ones, but Neumaier does not.
+        let v = vec![1.0, 1e100, 1.0, -1e100];
+        assert_eq!(kahan_sum(v.clone()), 0.0);
+        assert_eq!(neumaier_sum(v), 2.0);
+        assert_eq!(neumaier_sum(vec![0.5f32, 0.25]), 0.75);
+    }
+}